@@ -0,0 +1,43 @@
+use crate::WIKI_INDEX;
+use dioxus::prelude::*;
+use fermi::*;
+use std::path::Path;
+
+#[inline_props]
+pub fn Backlinks(cx: Scope, path: String) -> Element<'a> {
+    let index = use_read(cx, WIKI_INDEX);
+    let Some(index) = index else {
+        return None;
+    };
+    let backlinks = index.backlinks(Path::new(path));
+
+    cx.render(rsx! {
+        section {
+            class: "backlinks",
+            h2 { "Backlinks" }
+            if backlinks.is_empty() {
+                rsx! { p { "No other notes link here" } }
+            } else {
+                rsx! {
+                    ul {
+                        backlinks.iter().map(|page| {
+                            let path = page.path.display().to_string();
+                            rsx! {
+                                li {
+                                    key: "{path}",
+                                    // Opened by the preview script like any other wiki link
+                                    a {
+                                        class: "wiki-link",
+                                        href: "{path}",
+                                        "data-wiki-path": "{path}",
+                                        "{page.name}"
+                                    }
+                                }
+                            }
+                        })
+                    }
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,207 @@
+//! Top-level blocks of a rendered document. Live updates only replace the blocks
+//! that changed, so images and diagrams in the others aren't loaded again.
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const SPOS_ATTRIBUTE: &str = "data-spos=\"";
+
+/// Identifies a block across renders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockId {
+    pub start_line: u32,
+    /// Hash of the HTML with source lines relative to `start_line`,
+    /// a block moved by an edit above it keeps its key
+    pub key: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub id: BlockId,
+    pub html: String,
+}
+
+impl Block {
+    pub fn new(start_line: u32, html: String) -> Block {
+        let mut hasher = DefaultHasher::new();
+        let mut rest = html.as_str();
+        while let Some(at) = rest.find(SPOS_ATTRIBUTE) {
+            let (before, attribute) = rest.split_at(at + SPOS_ATTRIBUTE.len());
+            before.hash(&mut hasher);
+            let end = attribute.find('"').unwrap_or(attribute.len());
+            // `START-END` lines of blocks, `LINE:COLUMN-LINE:COLUMN` of inline elements
+            for position in attribute[..end].split('-') {
+                let (line, column) = position.split_once(':').unwrap_or((position, ""));
+                match line.parse::<u32>() {
+                    Ok(line) => line.wrapping_sub(start_line).hash(&mut hasher),
+                    Err(_) => line.hash(&mut hasher),
+                }
+                column.hash(&mut hasher);
+            }
+            rest = &attribute[end..];
+        }
+        rest.hash(&mut hasher);
+        Block {
+            id: BlockId {
+                start_line,
+                key: hasher.finish(),
+            },
+            html,
+        }
+    }
+}
+
+/// How a block of the new document is made
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BlockPatch<'a> {
+    /// The old block at `index`, its source lines moved by `shift`
+    Keep {
+        index: usize,
+        shift: i64,
+    },
+    Insert {
+        html: &'a str,
+    },
+}
+
+/// Patches turning the `old` blocks into the `new` ones, in the order of the new blocks.
+/// Kept blocks stay in order, so they never have to be moved in the DOM.
+pub fn diff<'a>(old: &[BlockId], new: &'a [Block]) -> Vec<BlockPatch<'a>> {
+    let same = |o: &BlockId, n: &Block| o.key == n.id.key;
+    let prefix = old.iter().zip(new).take_while(|(o, n)| same(o, n)).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| same(o, n))
+        .count();
+    let old_end = old.len() - suffix;
+
+    let keep = |index: usize, block: &Block| BlockPatch::Keep {
+        index,
+        shift: i64::from(block.id.start_line) - i64::from(old[index].start_line),
+    };
+    let mut patches = Vec::with_capacity(new.len());
+    let mut next = prefix;
+    for (i, block) in new.iter().enumerate() {
+        let index = if i < prefix {
+            Some(i)
+        } else if i >= new.len() - suffix {
+            Some(old_end + i - (new.len() - suffix))
+        } else {
+            let found = old[next..old_end].iter().position(|o| same(o, block));
+            found.map(|found| next + found)
+        };
+        match index {
+            Some(index) => {
+                next = index + 1;
+                patches.push(keep(index, block));
+            }
+            None => patches.push(BlockPatch::Insert { html: &block.html }),
+        }
+    }
+    patches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, Block, BlockId, BlockPatch};
+
+    fn ids(blocks: &[Block]) -> Vec<BlockId> {
+        blocks.iter().map(|b| b.id).collect()
+    }
+
+    #[test]
+    fn block_keys() {
+        let moved = Block::new(
+            3,
+            "<p data-spos=\"3-4\">a <em data-spos=\"4-4\">b</em></p>".into(),
+        );
+        let block = Block::new(
+            1,
+            "<p data-spos=\"1-2\">a <em data-spos=\"2-2\">b</em></p>".into(),
+        );
+        assert_eq!(moved.id.key, block.id.key);
+        let changed = Block::new(
+            1,
+            "<p data-spos=\"1-2\">a <em data-spos=\"1-1\">b</em></p>".into(),
+        );
+        assert_ne!(changed.id.key, block.id.key);
+        assert_ne!(Block::new(1, "<p>c</p>".into()).id.key, block.id.key);
+
+        let inline = |line: u32, column: u32| {
+            let html = format!(
+                "<p data-spos=\"{0}-{0}\"><span data-spos=\"{0}:{1}-{0}:{1}\">a</span></p>",
+                line, column
+            );
+            Block::new(line, html).id.key
+        };
+        assert_eq!(inline(1, 3), inline(5, 3));
+        assert_ne!(inline(1, 3), inline(1, 4));
+    }
+
+    #[test]
+    fn block_patches() {
+        let block = |line: u32, text: &str| {
+            Block::new(
+                line,
+                format!("<p data-spos=\"{}-{}\">{}</p>", line, line, text),
+            )
+        };
+        let old = [block(1, "a"), block(3, "b"), block(5, "c"), block(7, "d")];
+
+        let new = [block(1, "a"), block(3, "B"), block(5, "c"), block(7, "d")];
+        assert_eq!(
+            diff(&ids(&old), &new),
+            vec![
+                BlockPatch::Keep { index: 0, shift: 0 },
+                BlockPatch::Insert { html: &new[1].html },
+                BlockPatch::Keep { index: 2, shift: 0 },
+                BlockPatch::Keep { index: 3, shift: 0 },
+            ]
+        );
+
+        // Inserted lines move the blocks below
+        let new = [
+            block(1, "a"),
+            block(3, "new"),
+            block(5, "b"),
+            block(7, "c"),
+            block(9, "d"),
+        ];
+        assert_eq!(
+            diff(&ids(&old), &new),
+            vec![
+                BlockPatch::Keep { index: 0, shift: 0 },
+                BlockPatch::Insert { html: &new[1].html },
+                BlockPatch::Keep { index: 1, shift: 2 },
+                BlockPatch::Keep { index: 2, shift: 2 },
+                BlockPatch::Keep { index: 3, shift: 2 },
+            ]
+        );
+
+        // Removed blocks are dropped, one of swapped blocks is rendered again
+        let new = [block(1, "c"), block(3, "b"), block(5, "d")];
+        assert_eq!(
+            diff(&ids(&old), &new),
+            vec![
+                BlockPatch::Keep {
+                    index: 2,
+                    shift: -4
+                },
+                BlockPatch::Insert { html: &new[1].html },
+                BlockPatch::Keep {
+                    index: 3,
+                    shift: -2
+                },
+            ]
+        );
+
+        assert_eq!(
+            diff(&[], &new[..1]),
+            vec![BlockPatch::Insert { html: &new[0].html }]
+        );
+        assert_eq!(diff(&ids(&old), &[]), vec![]);
+    }
+}
@@ -0,0 +1,158 @@
+//! Typed messages between Rust and the preview script.
+//! Rust calls `crabix.receive(message)`, the script sends messages back
+//! through the hidden `crabix-channel` input.
+use crate::blocks::BlockPatch;
+use crate::keybindings::Keybindings;
+use crate::markdown_parser::Spos;
+use serde::{Deserialize, Serialize};
+
+/// Bumped with incompatible message changes, the preview script reports its version when loaded
+pub const VERSION: u32 = 2;
+
+/// Rust to preview
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToPreview<'a> {
+    Configure {
+        keybindings: &'a Keybindings,
+    },
+    /// Replaces the rendered blocks of the `base` generation, `None` replaces everything
+    Patch {
+        base: Option<u64>,
+        generation: u64,
+        blocks: Vec<BlockPatch<'a>>,
+    },
+    /// Brings the cursor `line` inside the `spos` block to the `anchor`,
+    /// a fraction of the window height. With a `column` the word at the cursor
+    /// is brought there instead.
+    ScrollTo {
+        spos: Spos,
+        line: u32,
+        column: Option<u32>,
+        /// Only scrolls when the target is outside the window
+        reveal_only: bool,
+        /// Where the line is in the block, as a fraction of the block height
+        block_fraction: f64,
+        anchor: f64,
+        smooth: bool,
+    },
+    /// Marks the block at the editor cursor, `None` clears the mark.
+    /// With a `column` the word at the cursor is marked too.
+    Highlight {
+        spos: Option<Spos>,
+        line: u32,
+        column: Option<u32>,
+    },
+    ScrollToFragment {
+        id: &'a str,
+    },
+    /// Pixels from the top of the document
+    ScrollToOffset {
+        offset: f64,
+    },
+    /// Commands handled by the preview script, like `scroll_down`
+    RunCommand {
+        name: &'a str,
+    },
+    FocusCommandPalette,
+}
+
+impl ToPreview<'_> {
+    /// JavaScript delivering the message, `</` would end the head script element
+    pub fn script(&self) -> String {
+        let json = serde_json::to_string(self).unwrap().replace("</", "<\\/");
+        format!("crabix.receive({});", json)
+    }
+}
+
+/// Preview to Rust
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FromPreview {
+    /// Sent when the script is loaded
+    Ready { version: u32 },
+    /// Task list checkbox at the source line is clicked
+    ToggleTask { line: u32 },
+    /// Wiki link is clicked
+    OpenFile {
+        path: String,
+        fragment: Option<String>,
+    },
+    /// Keybinding of a command handled by the application
+    RunCommand { name: String },
+    /// Document is scrolled to, sent once scrolling stops
+    Scroll { offset: f64 },
+    /// Rendered blocks aren't the ones a patch is based on, everything has to be sent again
+    Resync,
+}
+
+impl FromPreview {
+    pub fn parse(message: &str) -> Result<FromPreview, String> {
+        serde_json::from_str(message).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FromPreview, ToPreview, VERSION};
+    use crate::blocks::BlockPatch;
+    use crate::markdown_parser::Spos;
+    use crate::preview_script::PREVIEW_SCRIPT;
+
+    #[test]
+    fn messages() {
+        let scroll = ToPreview::ScrollTo {
+            spos: Spos {
+                start_line: 3,
+                end_line: 5,
+            },
+            line: 4,
+            column: Some(2),
+            reveal_only: false,
+            block_fraction: 0.5,
+            anchor: 0.5,
+            smooth: true,
+        };
+        assert_eq!(
+            scroll.script(),
+            r#"crabix.receive({"type":"scroll_to","spos":{"start_line":3,"end_line":5},"line":4,"column":2,"reveal_only":false,"block_fraction":0.5,"anchor":0.5,"smooth":true});"#
+        );
+        assert_eq!(
+            ToPreview::ScrollToFragment { id: "</script>" }.script(),
+            r#"crabix.receive({"type":"scroll_to_fragment","id":"<\/script>"});"#
+        );
+        let patch = ToPreview::Patch {
+            base: Some(1),
+            generation: 2,
+            blocks: vec![
+                BlockPatch::Keep { index: 0, shift: 1 },
+                BlockPatch::Insert { html: "<p>a</p>" },
+            ],
+        };
+        assert_eq!(
+            patch.script(),
+            r#"crabix.receive({"type":"patch","base":1,"generation":2,"blocks":[{"op":"keep","index":0,"shift":1},{"op":"insert","html":"<p>a<\/p>"}]});"#
+        );
+        assert_eq!(
+            ToPreview::FocusCommandPalette.script(),
+            r#"crabix.receive({"type":"focus_command_palette"});"#
+        );
+
+        assert_eq!(
+            FromPreview::parse(r#"{"type":"toggle_task","line":7}"#),
+            Ok(FromPreview::ToggleTask { line: 7 })
+        );
+        assert_eq!(
+            FromPreview::parse(r#"{"type":"open_file","path":"Notes.md","fragment":null}"#),
+            Ok(FromPreview::OpenFile {
+                path: "Notes.md".to_string(),
+                fragment: None
+            })
+        );
+        assert!(FromPreview::parse(r#"{"type":"toggle_task"}"#).is_err());
+        assert!(FromPreview::parse("7").is_err());
+
+        // The script is loaded from the same binary, but an edit can still miss one side
+        assert!(PREVIEW_SCRIPT.contains(&format!("const VERSION = {};", VERSION)));
+    }
+}
@@ -0,0 +1,113 @@
+//! Problems reported by `crabix-desktop check`
+use crate::markdown_parser::front_matter::FrontMatterBlock;
+use crate::markdown_parser::heading_anchor::HeadingsExt;
+use crate::markdown_parser::wiki_link::WikiLink;
+use crate::markdown_parser::MarkdownParser;
+use markdown_it::common::sourcemap::SourceWithLineStarts;
+use markdown_it::parser::core::Root;
+use markdown_it::plugins::cmark::inline::link::Link;
+use markdown_it::Node;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// 1-based source line
+    pub line: u32,
+    pub message: String,
+}
+
+/// Invalid front matter, links to missing headings and,
+/// when `wiki` is set, wiki links to missing pages
+pub fn check_document(parser: &mut MarkdownParser, content: &str, wiki: bool) -> Vec<Problem> {
+    let ast = parser.parse(content);
+    let root = ast.cast::<Root>().unwrap();
+    let slugs: Vec<&str> = root
+        .ext
+        .get::<HeadingsExt>()
+        .unwrap()
+        .headings
+        .iter()
+        .map(|heading| heading.slug.as_str())
+        .collect();
+    let mapping = SourceWithLineStarts::new(content);
+    let line = |node: &Node| {
+        node.srcmap
+            .map(|map| map.get_positions(&mapping).0 .0)
+            .unwrap_or(1)
+    };
+    let missing_heading = |fragment: &str| !fragment.is_empty() && !slugs.contains(&fragment);
+
+    let mut problems = vec![];
+    ast.walk(|node, _| {
+        if let Some(block) = node.cast::<FrontMatterBlock>() {
+            if let Some(error) = &block.front_matter.error {
+                problems.push(Problem {
+                    line: line(node),
+                    message: format!("invalid front matter: {}", error),
+                });
+            }
+        } else if let Some(link) = node.cast::<Link>() {
+            if let Some(fragment) = link.url.strip_prefix('#') {
+                if missing_heading(fragment) {
+                    problems.push(Problem {
+                        line: line(node),
+                        message: format!("link to a missing heading #{}", fragment),
+                    });
+                }
+            }
+        } else if let Some(link) = node.cast::<WikiLink>() {
+            let href = link.href();
+            if link.page.is_empty() {
+                let fragment = href.as_deref().and_then(|h| h.strip_prefix('#'));
+                if fragment.is_some_and(missing_heading) {
+                    problems.push(Problem {
+                        line: line(node),
+                        message: format!("wiki link to a missing heading #{}", fragment.unwrap()),
+                    });
+                }
+            } else if wiki && link.path.is_none() {
+                problems.push(Problem {
+                    line: line(node),
+                    message: format!("wiki link to a missing page \"{}\"", link.page),
+                });
+            }
+        }
+    });
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_document, Problem};
+    use crate::markdown_parser::wiki_link::WikiPages;
+    use crate::markdown_parser::MarkdownParser;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn document_problems() {
+        let content = "---\ntitle: [\n---\n# Setup\n\nSee [setup](#setup), [usage](#usage)\nand [[Install]], [[Notes]], [[#Setup]], [[#Nope]]\n";
+        let mut pages = WikiPages::default();
+        pages.insert(Path::new("Install.md"), PathBuf::from("/notes/Install.md"));
+        let parser = &mut MarkdownParser::new();
+        parser.set_wiki_pages(pages);
+
+        let problems = check_document(parser, content, true);
+        let problems: Vec<(u32, &str)> = problems
+            .iter()
+            .map(|Problem { line, message }| (*line, message.as_str()))
+            .collect();
+        assert_eq!(problems.len(), 4);
+        assert_eq!(problems[0].0, 1);
+        assert!(problems[0].1.starts_with("invalid front matter: "));
+        assert_eq!(
+            problems[1..],
+            [
+                (6, "link to a missing heading #usage"),
+                (7, "wiki link to a missing page \"Notes\""),
+                (7, "wiki link to a missing heading #nope"),
+            ]
+        );
+
+        let parser = &mut MarkdownParser::new();
+        assert!(check_document(parser, "[[Anything]]", false).is_empty());
+    }
+}
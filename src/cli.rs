@@ -0,0 +1,239 @@
+//! Command line arguments, `--help` and shell completions
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use std::io;
+use std::path::PathBuf;
+
+const NAME: &str = "crabix-desktop";
+
+#[derive(Debug, Parser)]
+#[command(name = NAME, version, about, args_override_self = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Markdown files or a directory to open as a workspace
+    pub files: Vec<String>,
+    /// Config file to read
+    #[arg(long = "config", value_name = "PATH", global = true)]
+    pub config_path: Option<PathBuf>,
+    #[command(flatten)]
+    settings: Settings,
+}
+
+#[derive(Debug, PartialEq, Subcommand)]
+pub enum Command {
+    /// Render a markdown file to a standalone HTML page
+    Render {
+        /// Markdown file, `-` reads standard input
+        file: String,
+        /// Write to a file instead of standard output
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Show a file in the running instance
+    Send {
+        file: String,
+        /// Source line to scroll to
+        #[arg(long, value_name = "LINE", default_value_t = 1)]
+        line: u32,
+    },
+    /// Validate the config and report broken links
+    Check { files: Vec<String> },
+    /// Print a shell completion script
+    Completions { shell: clap_complete::Shell },
+}
+
+// Config settings given as flags, they override the config file and environment.
+// The values are checked by `Config::set` like the ones of the config file.
+#[derive(Debug, Args)]
+struct Settings {
+    /// Unix socket the editor plugins send to
+    #[arg(long, value_name = "PATH", global = true)]
+    socket: Option<String>,
+    /// Color theme: light or dark
+    #[arg(long, value_name = "THEME", global = true)]
+    theme: Option<String>,
+    /// Stylesheet applied after the built-in styles
+    #[arg(long, value_name = "PATH", global = true)]
+    css: Option<String>,
+    /// Font family list of the text
+    #[arg(long, value_name = "FONTS", global = true)]
+    font: Option<String>,
+    /// Font size of the text
+    #[arg(long, value_name = "PX", global = true)]
+    font_size: Option<String>,
+    /// Font family list of code
+    #[arg(long, value_name = "FONTS", global = true)]
+    mono_font: Option<String>,
+    /// Font size of code
+    #[arg(long, value_name = "PX", global = true)]
+    mono_font_size: Option<String>,
+    /// Width of the text: github, wide or full
+    #[arg(long, value_name = "WIDTH", global = true)]
+    content_width: Option<String>,
+    /// Mark the block at the editor cursor: none, bar or tint
+    #[arg(long, value_name = "STYLE", global = true)]
+    focus_highlight: Option<String>,
+    /// Highest heading level listed by [TOC], 1 to 6
+    #[arg(long, value_name = "LEVEL", global = true)]
+    toc_min_depth: Option<String>,
+    /// Lowest heading level listed by [TOC], 1 to 6
+    #[arg(long, value_name = "LEVEL", global = true)]
+    toc_max_depth: Option<String>,
+    /// Where live scrolling puts the cursor line: top, center or N%
+    #[arg(long, value_name = "ANCHOR", global = true)]
+    scroll_anchor: Option<String>,
+    /// Animate live scrolling
+    #[arg(long, value_name = "BOOL", global = true)]
+    smooth_scroll: Option<String>,
+    /// Scroll within long blocks
+    #[arg(long, value_name = "BOOL", global = true)]
+    scroll_interpolation: Option<String>,
+    /// off, error, warn, info, debug or trace
+    #[arg(long, value_name = "LEVEL", global = true)]
+    log_level: Option<String>,
+    /// Also log to crabix.log in the state directory
+    #[arg(long, global = true)]
+    log_file: bool,
+    /// Title of untitled documents
+    #[arg(long, value_name = "TITLE", global = true)]
+    window_title: Option<String>,
+    /// Window size and position
+    #[arg(long, value_name = "WxH+X+Y", global = true)]
+    geometry: Option<String>,
+    /// Notes folder wiki links are resolved against
+    #[arg(long, value_name = "DIR", global = true)]
+    wiki_root: Option<String>,
+}
+
+impl Cli {
+    /// Config keys and values given as flags
+    pub fn settings(&self) -> Vec<(&'static str, &str)> {
+        let s = &self.settings;
+        [
+            ("socket_path", &s.socket),
+            ("theme", &s.theme),
+            ("css", &s.css),
+            ("font", &s.font),
+            ("font_size", &s.font_size),
+            ("mono_font", &s.mono_font),
+            ("mono_font_size", &s.mono_font_size),
+            ("content_width", &s.content_width),
+            ("focus_highlight", &s.focus_highlight),
+            ("toc_min_depth", &s.toc_min_depth),
+            ("toc_max_depth", &s.toc_max_depth),
+            ("scroll_anchor", &s.scroll_anchor),
+            ("smooth_scroll", &s.smooth_scroll),
+            ("scroll_interpolation", &s.scroll_interpolation),
+            ("log_level", &s.log_level),
+            ("window_title", &s.window_title),
+            ("geometry", &s.geometry),
+            ("wiki_root", &s.wiki_root),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value.as_deref()?)))
+        .chain(s.log_file.then_some(("log_file", "true")))
+        .collect()
+    }
+}
+
+pub fn print_completions(shell: clap_complete::Shell) {
+    clap_complete::generate(shell, &mut Cli::command(), NAME, &mut io::stdout());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cli, Command};
+    use crate::config;
+    use clap::error::ErrorKind;
+    use clap::{CommandFactory, Parser};
+    use std::path::PathBuf;
+
+    fn cli(args: &str) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("crabix-desktop").chain(args.split_whitespace()))
+    }
+
+    #[test]
+    fn parse_args() {
+        Cli::command().debug_assert();
+
+        let parsed = cli("README.md notes.md --theme dark --socket=/tmp/s").unwrap();
+        assert_eq!(parsed.command, None);
+        assert_eq!(parsed.files, vec!["README.md", "notes.md"]);
+        assert_eq!(
+            parsed.settings(),
+            vec![("socket_path", "/tmp/s"), ("theme", "dark")]
+        );
+        assert!(cli("").unwrap().files.is_empty());
+        assert_eq!(
+            cli("--log-file").unwrap().settings(),
+            vec![("log_file", "true")]
+        );
+        assert_eq!(
+            cli("--theme dark --theme light").unwrap().settings(),
+            vec![("theme", "light")]
+        );
+
+        let parsed = cli("--config /etc/crabix.toml render -o out.html README.md").unwrap();
+        assert_eq!(parsed.config_path, Some(PathBuf::from("/etc/crabix.toml")));
+        assert_eq!(
+            parsed.command,
+            Some(Command::Render {
+                file: "README.md".into(),
+                output: Some(PathBuf::from("out.html"))
+            })
+        );
+        assert_eq!(
+            cli("send --line 42 README.md").unwrap().command,
+            Some(Command::Send {
+                file: "README.md".into(),
+                line: 42
+            })
+        );
+        let parsed = cli("check --log-level off a.md b.md").unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(Command::Check {
+                files: vec!["a.md".into(), "b.md".into()]
+            })
+        );
+        assert_eq!(parsed.settings(), vec![("log_level", "off")]);
+        assert_eq!(cli("-- render").unwrap().files, vec!["render"]);
+        assert_eq!(
+            cli("completions fish").unwrap().command,
+            Some(Command::Completions {
+                shell: clap_complete::Shell::Fish
+            })
+        );
+
+        assert_eq!(cli("-V").unwrap_err().kind(), ErrorKind::DisplayVersion);
+        assert_eq!(cli("send -h").unwrap_err().kind(), ErrorKind::DisplayHelp);
+        assert_eq!(
+            cli("--colour red").unwrap_err().kind(),
+            ErrorKind::UnknownArgument
+        );
+        assert!(cli("--theme").is_err());
+        assert!(cli("-o out.html").is_err());
+        assert!(cli("render").is_err());
+        assert!(cli("render a.md b.md").is_err());
+        assert!(cli("send --line x README.md").is_err());
+        assert!(cli("completions tcsh").is_err());
+    }
+
+    #[test]
+    fn settings_are_config_keys() {
+        let all = "--socket a --theme a --css a --font a --font-size a --mono-font a \
+            --mono-font-size a --content-width a --focus-highlight a --toc-min-depth a \
+            --toc-max-depth a --scroll-anchor a --smooth-scroll a --scroll-interpolation a \
+            --log-level a --log-file --window-title a --geometry a --wiki-root a";
+        let mut keys: Vec<&str> = cli(all)
+            .unwrap()
+            .settings()
+            .iter()
+            .map(|(key, _)| *key)
+            .collect();
+        let mut expected = config::SETTINGS.to_vec();
+        keys.sort();
+        expected.sort();
+        assert_eq!(keys, expected);
+    }
+}
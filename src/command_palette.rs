@@ -0,0 +1,197 @@
+use crate::commands::{fuzzy_score, Command, COMMANDS};
+use crate::keybindings::Keybindings;
+use crate::{COMMAND_PALETTE, WORKSPACE};
+use dioxus::html::input_data::keyboard_types::Key;
+use dioxus::prelude::*;
+use fermi::*;
+use std::cmp::Reverse;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaletteMode {
+    Commands,
+    OpenFile,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PaletteItem {
+    Command(&'static Command),
+    File { name: String, path: String },
+}
+
+fn matching_items(mode: PaletteMode, query: &str, files: &[(String, String)]) -> Vec<PaletteItem> {
+    let mut scored: Vec<(i32, PaletteItem)> = match mode {
+        PaletteMode::Commands => COMMANDS
+            .iter()
+            .filter(|command| command.name != "command_palette")
+            .filter_map(|command| {
+                let score =
+                    fuzzy_score(query, command.title).max(fuzzy_score(query, command.name))?;
+                Some((score, PaletteItem::Command(command)))
+            })
+            .collect(),
+        PaletteMode::OpenFile => files
+            .iter()
+            .filter_map(|(name, path)| {
+                let score = fuzzy_score(query, name)?;
+                Some((
+                    score,
+                    PaletteItem::File {
+                        name: name.clone(),
+                        path: path.clone(),
+                    },
+                ))
+            })
+            .collect(),
+    };
+    // Stable sort keeps the registry order for equal scores
+    scored.sort_by_key(|(score, _)| Reverse(*score));
+    let mut items: Vec<PaletteItem> = scored.into_iter().map(|(_, item)| item).collect();
+
+    // Any path can be typed in, not only workspace files
+    let query = query.trim();
+    if mode == PaletteMode::OpenFile && !query.is_empty() {
+        let typed = PaletteItem::File {
+            name: query.to_string(),
+            path: query.to_string(),
+        };
+        let position = if items.is_empty() { 0 } else { 1 };
+        items.insert(position, typed);
+    }
+    items
+}
+
+#[inline_props]
+pub fn CommandPalette<'a>(
+    cx: Scope<'a>,
+    on_command: EventHandler<'a, &'static str>,
+    on_open: EventHandler<'a, String>,
+) -> Element<'a> {
+    let mode = *use_read(cx, COMMAND_PALETTE);
+    let setMode = use_set(cx, COMMAND_PALETTE);
+    let keybindings = use_context::<Keybindings>(cx);
+    let workspace = use_read(cx, WORKSPACE);
+    let query = use_state(cx, String::new);
+    let selected = use_state(cx, || 0usize);
+
+    let Some(mode) = mode else {
+        if !query.get().is_empty() {
+            query.set(String::new());
+        }
+        return None;
+    };
+    let files: Vec<(String, String)> = workspace
+        .iter()
+        .flat_map(|workspace| {
+            workspace.files.iter().map(|path| {
+                let name = path.strip_prefix(&workspace.root).unwrap_or(path);
+                (name.display().to_string(), path.display().to_string())
+            })
+        })
+        .collect();
+    // Shared by the list and the keys that move the selection
+    let items = Rc::new(matching_items(mode, query.get(), &files));
+    let selectable = items.clone();
+    let current = (*selected.get()).min(items.len().saturating_sub(1));
+
+    let run = move |item: &PaletteItem| {
+        setMode(None);
+        match item {
+            PaletteItem::Command(command) => on_command.call(command.name),
+            PaletteItem::File { path, .. } => on_open.call(path.clone()),
+        }
+    };
+    let placeholder = match mode {
+        PaletteMode::Commands => "Type a command",
+        PaletteMode::OpenFile => "Type a file name or path",
+    };
+
+    cx.render(rsx! {
+        div {
+            class: "command-palette-backdrop",
+            onclick: move |_| setMode(None),
+        }
+        div {
+            class: "command-palette",
+            input {
+                class: "command-palette-input",
+                r#type: "text",
+                placeholder: "{placeholder}",
+                autofocus: true,
+                value: "{query}",
+                oninput: move |evt| {
+                    query.set(evt.value.clone());
+                    selected.set(0);
+                },
+                onkeydown: move |evt| match evt.key() {
+                    Key::ArrowDown => selected.set((current + 1).min(selectable.len().saturating_sub(1))),
+                    Key::ArrowUp => selected.set(current.saturating_sub(1)),
+                    Key::Escape => setMode(None),
+                    Key::Enter => {
+                        if let Some(item) = selectable.get(current) {
+                            run(item);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            ul {
+                items.iter().enumerate().map(|(i, item)| {
+                    let items = items.clone();
+                    let state = if i == current { "selected" } else { "" };
+                    let (key, title, hint) = match item {
+                        PaletteItem::Command(command) => (
+                            command.name.to_string(),
+                            command.title.to_string(),
+                            keybindings.as_ref().map(|k| k.labels(command.name).join(", ")).unwrap_or_default(),
+                        ),
+                        PaletteItem::File { name, path } => (path.clone(), name.clone(), String::new()),
+                    };
+                    rsx! {
+                        li {
+                            key: "{key}",
+                            class: "command-palette-item {state}",
+                            onclick: move |_| run(&items[i]),
+                            span { "{title}" }
+                            span { class: "command-palette-keys", "{hint}" }
+                        }
+                    }
+                })
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{matching_items, PaletteItem, PaletteMode};
+
+    #[test]
+    fn palette_items() {
+        let names = |mode, query| -> Vec<String> {
+            let files = vec![
+                ("README.md".to_string(), "/docs/README.md".to_string()),
+                (
+                    "guide/install.md".to_string(),
+                    "/docs/guide/install.md".to_string(),
+                ),
+            ];
+            matching_items(mode, query, &files)
+                .iter()
+                .map(|item| match item {
+                    PaletteItem::Command(command) => command.name.to_string(),
+                    PaletteItem::File { path, .. } => path.clone(),
+                })
+                .collect()
+        };
+        assert_eq!(names(PaletteMode::Commands, "zoom").len(), 3);
+        assert_eq!(names(PaletteMode::Commands, "toggle toc")[0], "toggle_toc");
+        assert!(!names(PaletteMode::Commands, "").contains(&"command_palette".to_string()));
+        assert_eq!(
+            names(PaletteMode::OpenFile, "inst"),
+            vec!["/docs/guide/install.md", "inst"]
+        );
+        assert_eq!(names(PaletteMode::OpenFile, "notes.md"), vec!["notes.md"]);
+        assert_eq!(names(PaletteMode::OpenFile, "").len(), 2);
+    }
+}
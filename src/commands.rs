@@ -0,0 +1,130 @@
+/// Where a command is executed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommandTarget {
+    /// Handled by the preview script without a round trip to Rust
+    Preview,
+    App,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Command {
+    /// Used in the `[keybindings]` config section
+    pub name: &'static str,
+    /// Shown in the command palette
+    pub title: &'static str,
+    pub target: CommandTarget,
+}
+
+const fn preview(name: &'static str, title: &'static str) -> Command {
+    Command {
+        name,
+        title,
+        target: CommandTarget::Preview,
+    }
+}
+
+const fn app(name: &'static str, title: &'static str) -> Command {
+    Command {
+        name,
+        title,
+        target: CommandTarget::App,
+    }
+}
+
+pub const COMMANDS: &[Command] = &[
+    app("command_palette", "Show all commands"),
+    app("open_file", "Open file"),
+    app("reload", "Reload the document"),
+    app("toggle_live_updates", "Pause or resume live updates"),
+    app("toggle_toc", "Toggle table of contents"),
+    app("toggle_theme", "Toggle light and dark theme"),
+    app("zoom_in", "Zoom in"),
+    app("zoom_out", "Zoom out"),
+    app("reset_zoom", "Reset zoom"),
+    app("export_html", "Export as HTML"),
+    preview("find", "Find in the document"),
+    preview("scroll_down", "Scroll down"),
+    preview("scroll_up", "Scroll up"),
+    preview("half_page_down", "Scroll down half a page"),
+    preview("half_page_up", "Scroll up half a page"),
+    preview("scroll_top", "Go to the top of the document"),
+    preview("scroll_bottom", "Go to the bottom of the document"),
+    preview("next_heading", "Jump to the next heading"),
+    preview("previous_heading", "Jump to the previous heading"),
+    app("close", "Close the window"),
+];
+
+pub fn find(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+/// Case insensitive subsequence match of the query in the text.
+/// Consecutive characters and matches at word starts score higher, `None` when there is no match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut text_chars = text.char_indices().peekable();
+    let mut previous_match: Option<usize> = None;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let q = q.to_lowercase().next()?;
+        loop {
+            let (i, c) = text_chars.next()?;
+            if c.to_lowercase().next() != Some(q) {
+                continue;
+            }
+            score += 1;
+            if previous_match.is_some_and(|p| p + 1 == i) {
+                score += 5;
+            }
+            let word_start = i == 0 || !text[..i].ends_with(char::is_alphanumeric);
+            if word_start {
+                score += 3;
+            }
+            previous_match = Some(i + c.len_utf8() - 1);
+            break;
+        }
+    }
+    // The greedy match above may scatter a query that is also a substring
+    let (query, text_lower) = (query.trim().to_lowercase(), text.to_lowercase());
+    if let Some(i) = text_lower.find(&query).filter(|_| !query.is_empty()) {
+        score += 10 * query.chars().count() as i32;
+        if i == 0 || !text_lower[..i].ends_with(char::is_alphanumeric) {
+            score += 10;
+        }
+    }
+    // Shorter texts are closer matches
+    Some(score * 100 - text.len() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, fuzzy_score, COMMANDS};
+
+    #[test]
+    fn unique_names() {
+        for command in COMMANDS {
+            assert_eq!(find(command.name), Some(command));
+        }
+        assert_eq!(find("explode"), None);
+    }
+
+    #[test]
+    fn fuzzy_matching() {
+        assert!(fuzzy_score("zi", "Zoom in").is_some());
+        assert!(fuzzy_score("TOC", "Toggle table of contents").is_some());
+        assert!(fuzzy_score("zx", "Zoom in").is_none());
+        assert!(fuzzy_score("", "Zoom in").is_some());
+
+        // Word starts and consecutive characters win
+        let best = |query: &str| {
+            COMMANDS
+                .iter()
+                .filter_map(|c| Some((fuzzy_score(query, c.title)?, c.name)))
+                .max()
+                .map(|(_, name)| name)
+        };
+        assert_eq!(best("zoom in"), Some("zoom_in"));
+        assert_eq!(best("exp"), Some("export_html"));
+        assert_eq!(best("theme"), Some("toggle_theme"));
+        assert_eq!(best("next"), Some("next_heading"));
+    }
+}
@@ -0,0 +1,464 @@
+use crate::logger::{parse_filter, LogFilter};
+use crate::markdown_parser::toc_placeholder::TocDepth;
+use crate::render_options::{
+    parse_font, parse_font_size, parse_toc_level, ContentWidth, FocusHighlight, RenderOptions,
+    Theme,
+};
+use crate::scroll_sync::{ScrollAnchor, ScrollSync};
+use crate::session::Geometry;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+/// Settings that can also be given as `CRABIX_<KEY>` environment variables
+/// and `--<key>` command line flags, with `-` in place of `_`
+pub const SETTINGS: &[&str] = &[
+    "socket_path",
+    "theme",
+    "css",
+    "font",
+    "font_size",
+    "mono_font",
+    "mono_font_size",
+    "content_width",
+    "focus_highlight",
+    "toc_min_depth",
+    "toc_max_depth",
+    "scroll_anchor",
+    "smooth_scroll",
+    "scroll_interpolation",
+    "log_level",
+    "log_file",
+    "window_title",
+    "geometry",
+    "wiki_root",
+];
+
+/// `~/.config/crabix/config.toml`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Unix socket the editor plugins send the buffer to
+    pub socket_path: PathBuf,
+    /// `light` or `dark`, front matter `theme` takes precedence
+    pub theme: String,
+    /// Stylesheet applied after the built-in styles
+    pub css: Option<PathBuf>,
+    /// CSS `font-family` list, e.g. `"Inter", sans-serif`
+    pub font: Option<String>,
+    /// In pixels
+    pub font_size: Option<u32>,
+    /// Font of code blocks and inline code
+    pub mono_font: Option<String>,
+    pub mono_font_size: Option<u32>,
+    /// `github`, `wide` or `full`
+    pub content_width: String,
+    /// Marks the block at the editor cursor: `none`, `bar` or `tint`
+    pub focus_highlight: String,
+    /// Heading levels listed by `[TOC]` markers, from 1 to 6
+    pub toc_min_depth: u8,
+    pub toc_max_depth: u8,
+    /// Where live scrolling puts the cursor line: `top`, `center` or a percentage like `30%`
+    pub scroll_anchor: String,
+    /// Animate live scrolling instead of jumping
+    pub smooth_scroll: bool,
+    /// Scroll within long blocks by the cursor line position in the block
+    pub scroll_interpolation: bool,
+    /// `off`, `error`, `warn`, `info`, `debug` or `trace`,
+    /// or `RUST_LOG` style directives like `warn,crabix_desktop::editor=trace`
+    pub log_level: String,
+    /// Also log to `crabix.log` in the state directory
+    pub log_file: bool,
+    /// Shown when the document has no title or first heading
+    pub window_title: String,
+    /// Window size and position, e.g. `1200x800+0+0`, the last geometry is restored when unset
+    pub geometry: Option<String>,
+    /// Notes folder wiki links are resolved against, the workspace directory when unset
+    pub wiki_root: Option<PathBuf>,
+    /// Key sequence to command, e.g. `"gg" = "scroll_top"`
+    pub keybindings: BTreeMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            socket_path: PathBuf::from("/tmp/crabix"),
+            theme: "light".to_string(),
+            css: None,
+            font: None,
+            font_size: None,
+            mono_font: None,
+            mono_font_size: None,
+            content_width: "full".to_string(),
+            focus_highlight: "none".to_string(),
+            toc_min_depth: 1,
+            toc_max_depth: 6,
+            scroll_anchor: "center".to_string(),
+            smooth_scroll: true,
+            scroll_interpolation: true,
+            log_level: "warn".to_string(),
+            log_file: false,
+            window_title: "Crabix Desktop".to_string(),
+            geometry: None,
+            wiki_root: None,
+            keybindings: BTreeMap::new(),
+        }
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("crabix").join("config.toml"))
+}
+
+/// `$XDG_STATE_HOME/crabix` or `~/.local/state/crabix`
+pub fn state_dir() -> Option<PathBuf> {
+    let state_dir = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?)
+            .join(".local")
+            .join("state"),
+    };
+    Some(state_dir.join("crabix"))
+}
+
+/// Problems with the environment variables
+#[derive(Debug, Default, PartialEq)]
+pub struct EnvProblems {
+    pub errors: Vec<String>,
+    /// Ignored variables that aren't meant for crabix only, like `RUST_LOG`
+    pub warnings: Vec<String>,
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" | "" => Ok(false),
+        _ => Err(format!("expected true or false, got \"{}\"", value)),
+    }
+}
+
+// Empty values unset optional paths
+fn optional_path(value: &str) -> Option<PathBuf> {
+    Some(PathBuf::from(value)).filter(|_| !value.is_empty())
+}
+
+impl Config {
+    pub fn parse(content: &str) -> Result<Config, String> {
+        toml::from_str(content).map_err(|e| e.message().to_string())
+    }
+
+    /// Missing config file is the default config
+    pub fn load(path: Option<&Path>) -> Result<Config, String> {
+        let Some(path) = path.map(Path::to_path_buf).or_else(config_path) else {
+            return Ok(Config::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                Config::parse(&content).map_err(|e| format!("{}: {}", path.display(), e))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    /// Overrides a setting by its config key
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "socket_path" => self.socket_path = PathBuf::from(value),
+            "theme" => self.theme = value.to_string(),
+            "css" => self.css = optional_path(value),
+            "font" => self.font = parse_font(value)?,
+            "font_size" => self.font_size = parse_font_size(value)?,
+            "mono_font" => self.mono_font = parse_font(value)?,
+            "mono_font_size" => self.mono_font_size = parse_font_size(value)?,
+            "content_width" => self.content_width = value.to_string(),
+            "focus_highlight" => self.focus_highlight = value.to_string(),
+            "toc_min_depth" => self.toc_min_depth = parse_toc_level(value)?,
+            "toc_max_depth" => self.toc_max_depth = parse_toc_level(value)?,
+            "scroll_anchor" => self.scroll_anchor = value.to_string(),
+            "smooth_scroll" => self.smooth_scroll = parse_bool(value)?,
+            "scroll_interpolation" => self.scroll_interpolation = parse_bool(value)?,
+            "log_level" => self.log_level = value.to_string(),
+            "log_file" => self.log_file = parse_bool(value)?,
+            "window_title" => self.window_title = value.to_string(),
+            "geometry" => self.geometry = Some(value.to_string()).filter(|g| !g.is_empty()),
+            "wiki_root" => self.wiki_root = optional_path(value),
+            _ => return Err(format!("unknown setting \"{}\"", key)),
+        }
+        Ok(())
+    }
+
+    /// Applies `RUST_LOG` and the `CRABIX_<KEY>` variables, e.g. `CRABIX_THEME=dark`.
+    /// `CRABIX_LOG_LEVEL` takes precedence over `RUST_LOG`, an invalid `RUST_LOG` is ignored.
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> EnvProblems {
+        let mut problems = EnvProblems::default();
+        if let Some(value) = var("RUST_LOG") {
+            match parse_filter(&value) {
+                Ok(_) => self.log_level = value,
+                Err(e) => problems.warnings.push(format!("RUST_LOG: {}, ignored", e)),
+            }
+        }
+        for key in SETTINGS {
+            let name = format!("CRABIX_{}", key.to_uppercase());
+            if let Some(value) = var(&name) {
+                if let Err(e) = self.set(key, &value) {
+                    problems.errors.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+        problems
+    }
+
+    /// Problems with the setting values, reported at startup
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = vec![];
+        if Theme::from_name(&self.theme).is_none() {
+            errors.push(format!(
+                "theme: unknown theme \"{}\", expected \"light\" or \"dark\"",
+                self.theme
+            ));
+        }
+        for (key, font) in [("font", &self.font), ("mono_font", &self.mono_font)] {
+            if let Some(Err(e)) = font.as_deref().map(parse_font) {
+                errors.push(format!("{}: {}", key, e));
+            }
+        }
+        for (key, size) in [
+            ("font_size", self.font_size),
+            ("mono_font_size", self.mono_font_size),
+        ] {
+            if let Some(Err(e)) = size.map(|size| parse_font_size(&size.to_string())) {
+                errors.push(format!("{}: {}", key, e));
+            }
+        }
+        if ContentWidth::from_name(&self.content_width).is_none() {
+            errors.push(format!(
+                "content_width: unknown content width \"{}\", expected github, wide or full",
+                self.content_width
+            ));
+        }
+        if FocusHighlight::from_name(&self.focus_highlight).is_none() {
+            errors.push(format!(
+                "focus_highlight: unknown focus highlight \"{}\", expected none, bar or tint",
+                self.focus_highlight
+            ));
+        }
+        for (key, level) in [
+            ("toc_min_depth", self.toc_min_depth),
+            ("toc_max_depth", self.toc_max_depth),
+        ] {
+            if let Err(e) = parse_toc_level(&level.to_string()) {
+                errors.push(format!("{}: {}", key, e));
+            }
+        }
+        if self.toc_min_depth > self.toc_max_depth {
+            errors.push(format!(
+                "toc_min_depth: {} is greater than toc_max_depth {}",
+                self.toc_min_depth, self.toc_max_depth
+            ));
+        }
+        if let Err(e) = ScrollAnchor::parse(&self.scroll_anchor) {
+            errors.push(format!("scroll_anchor: {}", e));
+        }
+        if let Err(e) = parse_filter(&self.log_level) {
+            errors.push(format!("log_level: {}", e));
+        }
+        if let Err(e) = self.geometry.as_deref().map(Geometry::parse).transpose() {
+            errors.push(format!("geometry: {}", e));
+        }
+        if self.socket_path.as_os_str().is_empty() {
+            errors.push("socket_path: must not be empty".to_string());
+        }
+        if let Some(css) = &self.css {
+            if !css.is_file() {
+                errors.push(format!("css: {} is not a file", css.display()));
+            }
+        }
+        if let Some(root) = &self.wiki_root {
+            if !root.is_dir() {
+                errors.push(format!("wiki_root: {} is not a directory", root.display()));
+            }
+        }
+        errors
+    }
+
+    pub fn geometry(&self) -> Option<Geometry> {
+        Geometry::parse(self.geometry.as_deref()?).ok()
+    }
+
+    pub fn theme(&self) -> Theme {
+        Theme::from_name(&self.theme).unwrap_or_default()
+    }
+
+    /// Display settings the preview starts with
+    pub fn render_options(&self) -> RenderOptions {
+        RenderOptions {
+            theme: self.theme(),
+            font: self.font.clone(),
+            font_size: self.font_size,
+            mono_font: self.mono_font.clone(),
+            mono_font_size: self.mono_font_size,
+            content_width: ContentWidth::from_name(&self.content_width).unwrap_or_default(),
+            focus_highlight: FocusHighlight::from_name(&self.focus_highlight).unwrap_or_default(),
+            toc_depth: TocDepth {
+                min: self.toc_min_depth,
+                max: self.toc_max_depth,
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn scroll_sync(&self) -> ScrollSync {
+        ScrollSync {
+            anchor: ScrollAnchor::parse(&self.scroll_anchor).unwrap_or(ScrollAnchor::Center),
+            smooth: self.smooth_scroll,
+            interpolate: self.scroll_interpolation,
+        }
+    }
+
+    pub fn log_filter(&self) -> LogFilter {
+        parse_filter(&self.log_level).unwrap_or_else(|_| parse_filter("").unwrap())
+    }
+
+    /// `None` unless logging to a file is enabled
+    pub fn log_file_path(&self) -> Option<PathBuf> {
+        Some(state_dir()?.join("crabix.log")).filter(|_| self.log_file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, EnvProblems};
+    use crate::markdown_parser::toc_placeholder::TocDepth;
+    use crate::render_options::{ContentWidth, Theme};
+    use crate::scroll_sync::{ScrollAnchor, ScrollSync};
+    use crate::session::Geometry;
+    use log::LevelFilter;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_config() {
+        let config =
+            Config::parse("[keybindings]\ngg = \"none\"\n\"ctrl+j\" = \"next_heading\"\n").unwrap();
+        assert_eq!(config.keybindings.len(), 2);
+        assert_eq!(config.keybindings["gg"], "none");
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert!(Config::parse("[keybinding]\n").is_err());
+
+        let config = Config::parse(
+            "socket_path = \"/run/user/1000/crabix\"\ntheme = \"dark\"\nlog_level = \"warn\"\nwindow_title = \"Notes\"\nfont_size = 18\ncontent_width = \"github\"\ngeometry = \"1200x800+0+0\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.socket_path, PathBuf::from("/run/user/1000/crabix"));
+        assert_eq!(config.theme(), Theme::Dark);
+        assert_eq!(config.log_filter().level, LevelFilter::Warn);
+        assert!(!config.log_file);
+        assert_eq!(config.window_title, "Notes");
+        assert_eq!(config.render_options().font_size, Some(18));
+        assert_eq!(config.render_options().content_width, ContentWidth::Github);
+        assert_eq!(config.render_options().toc_depth, TocDepth::default());
+
+        let depths = Config::parse("toc_min_depth = 2\ntoc_max_depth = 3\n").unwrap();
+        assert_eq!(
+            depths.render_options().toc_depth,
+            TocDepth { min: 2, max: 3 }
+        );
+        assert!(depths.validate().is_empty());
+        assert_eq!(config.geometry(), Geometry::parse("1200x800+0+0").ok());
+        assert_eq!(Config::default().geometry(), None);
+        assert!(config.validate().is_empty());
+        assert!(Config::parse("theme = 1\n").is_err());
+    }
+
+    #[test]
+    fn overrides() {
+        let mut config = Config::parse("theme = \"dark\"\ncss = \"/tmp/style.css\"\n").unwrap();
+        let problems = config.apply_env(|var| match var {
+            "CRABIX_THEME" => Some("light".to_string()),
+            "CRABIX_CSS" => Some(String::new()),
+            "CRABIX_WIKI_ROOT" => Some("/tmp".to_string()),
+            "CRABIX_LOG_FILE" => Some("1".to_string()),
+            "CRABIX_SCROLL_ANCHOR" => Some("25%".to_string()),
+            "CRABIX_SMOOTH_SCROLL" => Some("off".to_string()),
+            "RUST_LOG" => Some("info,crabix_desktop::editor=trace".to_string()),
+            _ => None,
+        });
+        assert_eq!(problems, EnvProblems::default());
+        assert_eq!(config.theme(), Theme::Light);
+        assert_eq!(config.css, None);
+        assert_eq!(config.wiki_root, Some(PathBuf::from("/tmp")));
+        assert!(config.log_file);
+        assert_eq!(
+            config.scroll_sync(),
+            ScrollSync {
+                anchor: ScrollAnchor::Percent(25),
+                smooth: false,
+                interpolate: true
+            }
+        );
+        assert_eq!(config.log_filter().level, LevelFilter::Info);
+        assert_eq!(config.log_filter().modules.len(), 1);
+
+        let problems = config.apply_env(|var| match var {
+            "RUST_LOG" => Some("info".to_string()),
+            "CRABIX_LOG_LEVEL" => Some("error".to_string()),
+            "CRABIX_LOG_FILE" => Some("sometimes".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.log_filter().level, LevelFilter::Error);
+        assert_eq!(
+            problems.errors,
+            vec!["CRABIX_LOG_FILE: expected true or false, got \"sometimes\""]
+        );
+
+        // `RUST_LOG` exported for other tools doesn't stop the preview
+        let mut config = Config::default();
+        let problems = config.apply_env(|var| match var {
+            "RUST_LOG" => Some("hyper=verbose".to_string()),
+            _ => None,
+        });
+        assert!(problems.errors.is_empty());
+        assert_eq!(
+            problems.warnings,
+            vec!["RUST_LOG: unknown level \"verbose\", expected off, error, warn, info, debug or trace, ignored"]
+        );
+        assert_eq!(config.log_level, "warn");
+        assert!(config.validate().is_empty());
+        config.apply_env(|var| match var {
+            "RUST_LOG" => Some("info,crabix_desktop".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.log_filter().level, LevelFilter::Info);
+
+        config.set("log_level", "warn,tokio=loud").unwrap();
+        config.set("theme", "blue").unwrap();
+        config.set("wiki_root", "/nonexistent/notes").unwrap();
+        config.set("geometry", "wide").unwrap();
+        config.set("content_width", "narrow").unwrap();
+        config.set("scroll_anchor", "bottom").unwrap();
+        config.set("toc_min_depth", "4").unwrap();
+        config.set("toc_max_depth", "2").unwrap();
+        assert!(config.set("toc_max_depth", "7").is_err());
+        config.font_size = Some(200);
+        assert!(config.set("font_size", "huge").is_err());
+        assert!(config.set("colour", "red").is_err());
+        assert_eq!(
+            config.validate(),
+            vec![
+                "theme: unknown theme \"blue\", expected \"light\" or \"dark\"",
+                "font_size: invalid font size \"200\", expected pixels from 6 to 72",
+                "content_width: unknown content width \"narrow\", expected github, wide or full",
+                "toc_min_depth: 4 is greater than toc_max_depth 2",
+                "scroll_anchor: invalid scroll anchor \"bottom\", expected top, center or a percentage like 30%",
+                "log_level: unknown level \"loud\", expected off, error, warn, info, debug or trace",
+                "geometry: invalid geometry \"wide\", expected WIDTHxHEIGHT or WIDTHxHEIGHT+X+Y",
+                "wiki_root: /nonexistent/notes is not a directory",
+            ]
+        );
+    }
+}
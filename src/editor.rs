@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::sync::Mutex;
+
+/// Write half of the last editor connection accepted by the unix socket listener.
+/// Used to send edits made in the preview back to the editor.
+pub type EditorConnection = Arc<Mutex<Option<OwnedWriteHalf>>>;
+
+/// Notifications are sent to the editor as JSON lines:
+/// `{"method":"toggle_task","params":{"line":3,"checked":true}}`
+#[derive(Debug, Serialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum EditorNotification {
+    ToggleTask { line: u32, checked: bool },
+}
+
+/// Requests are sent to the unix socket as a JSON object in place of the content:
+/// `{"method":"set","params":{"key":"font_size","value":"18"}}`
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum EditorRequest {
+    /// 1.0 is the normal size
+    Zoom { level: f64 },
+    /// Changes a display setting: `theme`, `font`, `font_size`, `mono_font`,
+    /// `mono_font_size`, `content_width` or `focus_highlight`
+    Set { key: String, value: String },
+}
+
+/// `None` unless the message is a JSON object
+pub fn parse_request(msg: &str) -> Option<Result<EditorRequest, String>> {
+    if !msg.trim_start().starts_with('{') {
+        return None;
+    }
+    Some(serde_json::from_str(msg).map_err(|e| e.to_string()))
+}
+
+/// Content sent to the unix socket: `LINE[:COLUMN][@BUFFER] CONTENT`,
+/// e.g. `12:5@3 # Notes` is line 12, column 5 of the editor buffer 3
+#[derive(Debug, Clone, PartialEq)]
+pub struct Update<'a> {
+    /// Line the editor cursor is on
+    pub line: u32,
+    /// Character the editor cursor is on, counted from 1
+    pub column: Option<u32>,
+    /// Buffer name or number without spaces, shown in the window title
+    pub buffer: Option<&'a str>,
+    pub content: &'a str,
+}
+
+// Leading digits as a number and the rest of the message
+fn split_number(msg: &str) -> Option<(u32, &str)> {
+    let digits = msg.chars().take_while(|c| c.is_ascii_digit()).count();
+    let (number, rest) = msg.split_at(digits);
+    Some((number.parse().ok()?, rest))
+}
+
+pub fn parse_update(msg: &str) -> Option<Update<'_>> {
+    let (line, rest) = split_number(msg)?;
+    let (column, rest) = match rest.strip_prefix(':') {
+        Some(rest) => {
+            let (column, rest) = split_number(rest)?;
+            (Some(column), rest)
+        }
+        None => (None, rest),
+    };
+    let (buffer, rest) = match rest.strip_prefix('@') {
+        Some(rest) => {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (Some(&rest[..end]).filter(|b| !b.is_empty()), &rest[end..])
+        }
+        None => (None, rest),
+    };
+    // The separator after the header
+    let mut chars = rest.chars();
+    chars.next()?;
+    Some(Update {
+        line,
+        column,
+        buffer,
+        content: chars.as_str(),
+    })
+}
+
+/// `false` when no editor is connected or writing to it failed
+pub async fn notify(connection: &EditorConnection, notification: EditorNotification) -> bool {
+    let mut msg = serde_json::to_vec(&notification).unwrap();
+    msg.push(b'\n');
+
+    let mut connection = connection.lock().await;
+    match connection.as_mut() {
+        Some(stream) => {
+            if let Err(e) = stream.write_all(&msg).await {
+                log::warn!("Failed to notify editor: {}", e);
+                *connection = None;
+                return false;
+            }
+            true
+        }
+        None => {
+            log::warn!("No editor connected, {:?} is dropped", notification);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_request, parse_update, EditorRequest, Update};
+
+    #[test]
+    fn updates() {
+        assert_eq!(
+            parse_update("12 # Notes\n"),
+            Some(Update {
+                line: 12,
+                column: None,
+                buffer: None,
+                content: "# Notes\n"
+            })
+        );
+        assert_eq!(
+            parse_update("3@notes.md \n- [ ] task"),
+            Some(Update {
+                line: 3,
+                column: None,
+                buffer: Some("notes.md"),
+                content: "\n- [ ] task"
+            })
+        );
+        assert_eq!(
+            parse_update("12:5@3 # Notes"),
+            Some(Update {
+                line: 12,
+                column: Some(5),
+                buffer: Some("3"),
+                content: "# Notes"
+            })
+        );
+        assert_eq!(parse_update("4:1 text").unwrap().column, Some(1));
+        assert_eq!(parse_update("4: text"), None);
+        assert_eq!(parse_update("1 ").unwrap().content, "");
+        assert_eq!(parse_update("7@ text").unwrap().buffer, None);
+        assert_eq!(parse_update("# Notes"), None);
+        assert_eq!(parse_update("12"), None);
+    }
+    #[test]
+    fn requests() {
+        assert_eq!(
+            parse_request("{\"method\":\"zoom\",\"params\":{\"level\":1.5}}"),
+            Some(Ok(EditorRequest::Zoom { level: 1.5 }))
+        );
+        assert_eq!(
+            parse_request(
+                " {\"method\":\"set\",\"params\":{\"key\":\"font\",\"value\":\"serif\"}}"
+            ),
+            Some(Ok(EditorRequest::Set {
+                key: "font".to_string(),
+                value: "serif".to_string()
+            }))
+        );
+        assert!(parse_request("{\"method\":\"scroll\"}").unwrap().is_err());
+        assert_eq!(parse_request("12 {}"), None);
+    }
+}
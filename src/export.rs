@@ -0,0 +1,92 @@
+use crate::markdown_body_css::MARKDOWN_BODY_CSS;
+use crate::markdown_parser::front_matter;
+use crate::render_options::RenderOptions;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Marks exported pages, only they are overwritten by the next export
+const GENERATOR: &str = r#"<meta name="generator" content="crabix-desktop">"#;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Standalone HTML page with the rendered markdown and the preview styles
+pub fn html_document(content: &str, options: &RenderOptions, default_title: &str) -> String {
+    let front_matter = front_matter::extract(content);
+    let options = options.with_front_matter(front_matter.as_ref());
+    let title = front_matter
+        .and_then(|fm| fm.title)
+        .unwrap_or_else(|| default_title.to_string());
+    let body = options.parser().parse(content).render();
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+{generator}
+<title>{title}</title>
+<style>{css}</style>
+</head>
+<body>
+<div class="crabix-layout {theme}" style="{style}">
+<div class="markdown-body">
+{body}</div>
+</div>
+</body>
+</html>
+"#,
+        generator = GENERATOR,
+        title = escape(&title),
+        css = MARKDOWN_BODY_CSS,
+        theme = options.theme.class(),
+        style = escape(&options.style()),
+        body = body
+    )
+}
+
+fn is_export(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|html| html.contains(GENERATOR))
+}
+
+/// Where the export of a markdown file is written: `notes.html` next to `notes.md`,
+/// `notes-1.html` and so on when a page that isn't an export already has the name
+pub fn export_path(source: &Path) -> PathBuf {
+    let stem = source
+        .file_stem()
+        .map_or("export".into(), |stem| stem.to_string_lossy());
+    (0..)
+        .map(|n| match n {
+            0 => source.with_file_name(format!("{}.html", stem)),
+            n => source.with_file_name(format!("{}-{}.html", stem, n)),
+        })
+        .find(|path| !path.exists() || is_export(path))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_path, html_document};
+    use crate::render_options::RenderOptions;
+    use crate::test_util::TempDir;
+    use std::fs;
+
+    #[test]
+    fn export_paths() {
+        let dir = TempDir::new("export");
+        let source = dir.path().join("index.md");
+        assert_eq!(export_path(&source), dir.path().join("index.html"));
+
+        // Previous exports are replaced, other pages are kept
+        let export = html_document("# Index", &RenderOptions::default(), "index");
+        fs::write(dir.path().join("index.html"), &export).unwrap();
+        assert_eq!(export_path(&source), dir.path().join("index.html"));
+        fs::write(dir.path().join("index.html"), "<h1>Handwritten</h1>").unwrap();
+        assert_eq!(export_path(&source), dir.path().join("index-1.html"));
+        fs::write(dir.path().join("index-1.html"), "<h1>Also mine</h1>").unwrap();
+        assert_eq!(export_path(&source), dir.path().join("index-2.html"));
+    }
+}
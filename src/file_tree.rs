@@ -0,0 +1,93 @@
+use crate::workspace::FileTreeNode;
+use crate::{ContentSource, CONTENT_SOURCE, WORKSPACE};
+use dioxus::prelude::*;
+use fermi::*;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+// Flattens the expanded part of the tree into (depth, node) rows
+fn visible_rows<'t>(
+    nodes: &'t [FileTreeNode],
+    depth: usize,
+    collapsed: &HashSet<PathBuf>,
+    rows: &mut Vec<(usize, &'t FileTreeNode)>,
+) {
+    for node in nodes {
+        rows.push((depth, node));
+        if let FileTreeNode::Dir { path, children, .. } = node {
+            if !collapsed.contains(path) {
+                visible_rows(children, depth + 1, collapsed, rows);
+            }
+        }
+    }
+}
+
+#[inline_props]
+pub fn FileTree<'a>(cx: Scope<'a>, on_open: EventHandler<'a, String>) -> Element<'a> {
+    let collapsed = use_ref(cx, HashSet::<PathBuf>::new);
+    let workspace = use_read(cx, WORKSPACE);
+    let Some(workspace) = workspace else {
+        return None;
+    };
+    let current = match use_read(cx, CONTENT_SOURCE) {
+        Some(ContentSource::File(path)) => Some(path.as_str()),
+        _ => None,
+    };
+    let root_name = workspace
+        .root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tree = workspace.tree();
+    let mut rows = vec![];
+    visible_rows(&tree, 0, &collapsed.read(), &mut rows);
+
+    cx.render(rsx! {
+        nav {
+            class: "file-tree",
+            div { class: "file-tree-root", title: "{workspace.root.display()}", "{root_name}" }
+            if rows.is_empty() {
+                rsx! { p { class: "file-tree-empty", "No markdown files" } }
+            }
+            ul {
+                rows.into_iter().map(|(depth, node)| {
+                    let path = node.path().to_path_buf();
+                    let key = path.display().to_string();
+                    let indent = depth * 12;
+                    match node {
+                        FileTreeNode::Dir { name, .. } => {
+                            let state = if collapsed.read().contains(&path) { "collapsed" } else { "" };
+                            rsx! {
+                                li {
+                                    key: "{key}/",
+                                    class: "file-tree-dir {state}",
+                                    style: "padding-left: {indent}px",
+                                    onclick: move |_| {
+                                        let mut collapsed = collapsed.write();
+                                        if !collapsed.remove(&path) {
+                                            collapsed.insert(path.clone());
+                                        }
+                                    },
+                                    "{name}"
+                                }
+                            }
+                        }
+                        FileTreeNode::File { name, .. } => {
+                            let state = if current == Some(key.as_str()) { "current" } else { "" };
+                            rsx! {
+                                li {
+                                    key: "{key}",
+                                    class: "file-tree-file {state}",
+                                    style: "padding-left: {indent}px",
+                                    title: "{key}",
+                                    onclick: move |_| on_open.call(key.clone()),
+                                    "{name}"
+                                }
+                            }
+                        }
+                    }
+                })
+            }
+        }
+    })
+}
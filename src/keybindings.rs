@@ -0,0 +1,304 @@
+use crate::commands;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Vim-like defaults, overridden by the `[keybindings]` config section
+const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("j", "scroll_down"),
+    ("down", "scroll_down"),
+    ("k", "scroll_up"),
+    ("up", "scroll_up"),
+    ("ctrl+d", "half_page_down"),
+    ("ctrl+u", "half_page_up"),
+    ("gg", "scroll_top"),
+    ("G", "scroll_bottom"),
+    ("]]", "next_heading"),
+    ("[[", "previous_heading"),
+    ("/", "find"),
+    ("ctrl+f", "find"),
+    ("q", "close"),
+    ("ctrl+shift+p", "command_palette"),
+    ("ctrl+=", "zoom_in"),
+    ("ctrl++", "zoom_in"),
+    ("ctrl+-", "zoom_out"),
+    ("ctrl+0", "reset_zoom"),
+];
+
+// Config names of keys that aren't characters and their `KeyboardEvent.key` values
+const NAMED_KEYS: &[(&str, &str)] = &[
+    ("enter", "Enter"),
+    ("escape", "Escape"),
+    ("esc", "Escape"),
+    ("space", " "),
+    ("tab", "Tab"),
+    ("backspace", "Backspace"),
+    ("delete", "Delete"),
+    ("up", "ArrowUp"),
+    ("down", "ArrowDown"),
+    ("left", "ArrowLeft"),
+    ("right", "ArrowRight"),
+    ("pageup", "PageUp"),
+    ("pagedown", "PageDown"),
+    ("home", "Home"),
+    ("end", "End"),
+    ("f1", "F1"),
+    ("f2", "F2"),
+    ("f3", "F3"),
+    ("f4", "F4"),
+    ("f5", "F5"),
+    ("f6", "F6"),
+    ("f7", "F7"),
+    ("f8", "F8"),
+    ("f9", "F9"),
+    ("f10", "F10"),
+    ("f11", "F11"),
+    ("f12", "F12"),
+];
+
+/// Key sequence bound to a command, e.g. `gg` -> `["g", "g"]`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Keybinding {
+    pub keys: Vec<String>,
+    pub command: String,
+}
+
+/// Parses a key in the config notation into the form the preview script matches
+/// keyboard events against: `ctrl+alt+meta+shift+` modifiers followed by `KeyboardEvent.key`.
+/// Shift is folded into character keys, `shift+g` is `G`.
+fn parse_key(key: &str) -> Result<String, String> {
+    let mut parts: Vec<&str> = key.split('+').collect();
+    // `ctrl++` binds the plus key
+    if key.ends_with("++") {
+        parts.truncate(parts.len() - 2);
+        parts.push("+");
+    }
+    let (name, modifiers) = parts.split_last().ok_or("empty key")?;
+    let (mut ctrl, mut alt, mut meta, mut shift) = (false, false, false, false);
+    for modifier in modifiers {
+        match modifier.to_lowercase().as_str() {
+            "ctrl" | "control" => ctrl = true,
+            "alt" => alt = true,
+            "meta" | "cmd" | "super" => meta = true,
+            "shift" => shift = true,
+            _ => return Err(format!("unknown modifier \"{}\" in \"{}\"", modifier, key)),
+        }
+    }
+
+    let mut name = if name.chars().count() == 1 {
+        name.to_string()
+    } else {
+        NAMED_KEYS
+            .iter()
+            .find(|(config, _)| config.eq_ignore_ascii_case(name))
+            .map(|(_, key)| key.to_string())
+            .ok_or_else(|| format!("unknown key \"{}\"", name))?
+    };
+    if name.chars().count() == 1 {
+        if shift {
+            name = name.to_uppercase();
+        }
+        shift = false;
+    }
+
+    let mut normalized = String::new();
+    for (enabled, modifier) in [
+        (ctrl, "ctrl+"),
+        (alt, "alt+"),
+        (meta, "meta+"),
+        (shift, "shift+"),
+    ] {
+        if enabled {
+            normalized.push_str(modifier);
+        }
+    }
+    normalized.push_str(&name);
+    Ok(normalized)
+}
+
+/// Parses a space separated key sequence, e.g. `g g` or `ctrl+k ctrl+t`.
+/// Words without modifiers that aren't key names are split into characters, `gg` is `g g`.
+pub fn parse_sequence(sequence: &str) -> Result<Vec<String>, String> {
+    let mut keys = vec![];
+    for word in sequence.split_whitespace() {
+        let named = NAMED_KEYS
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case(word));
+        if word.contains('+') && word.len() > 1 || named {
+            keys.push(parse_key(word)?);
+        } else {
+            keys.extend(word.chars().map(|c| c.to_string()));
+        }
+    }
+    if keys.is_empty() {
+        return Err("empty key sequence".into());
+    }
+    Ok(keys)
+}
+
+impl Keybinding {
+    /// Human readable keys, e.g. `Ctrl+Shift+P` or `g g`
+    pub fn label(&self) -> String {
+        let labels: Vec<String> = self
+            .keys
+            .iter()
+            .map(|key| {
+                let mut name = key.as_str();
+                let mut label = String::new();
+                for (prefix, modifier) in [
+                    ("ctrl+", "Ctrl+"),
+                    ("alt+", "Alt+"),
+                    ("meta+", "Meta+"),
+                    ("shift+", "Shift+"),
+                ] {
+                    if name.len() > prefix.len() && name.starts_with(prefix) {
+                        name = &name[prefix.len()..];
+                        label.push_str(modifier);
+                    }
+                }
+                match name {
+                    " " => label.push_str("Space"),
+                    // Shift is folded into characters, `ctrl+P` is typed with Shift
+                    _ if !label.is_empty() && name.chars().count() == 1 => {
+                        if name.chars().any(char::is_uppercase) {
+                            label.push_str("Shift+");
+                        }
+                        label.push_str(&name.to_uppercase());
+                    }
+                    _ => label.push_str(name),
+                }
+                label
+            })
+            .collect();
+        labels.join(" ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Keybindings {
+    pub bindings: Vec<Keybinding>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .map(|(keys, command)| Keybinding {
+                keys: parse_sequence(keys).unwrap(),
+                command: command.to_string(),
+            })
+            .collect();
+        Keybindings { bindings }
+    }
+}
+
+impl Keybindings {
+    /// Labels of all key sequences bound to the command
+    pub fn labels(&self, command: &str) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.command == command)
+            .map(Keybinding::label)
+            .collect()
+    }
+
+    /// Defaults with the user bindings applied, binding a key to `"none"` removes it.
+    /// Invalid entries are skipped and reported.
+    pub fn with_overrides(overrides: &BTreeMap<String, String>) -> (Keybindings, Vec<String>) {
+        let mut keybindings = Keybindings::default();
+        let mut errors = vec![];
+        for (sequence, command) in overrides {
+            let keys = match parse_sequence(sequence) {
+                Ok(keys) => keys,
+                Err(e) => {
+                    errors.push(format!("keybinding \"{}\": {}", sequence, e));
+                    continue;
+                }
+            };
+            if command != "none" && commands::find(command).is_none() {
+                errors.push(format!(
+                    "keybinding \"{}\": unknown command \"{}\"",
+                    sequence, command
+                ));
+                continue;
+            }
+            keybindings.bindings.retain(|binding| binding.keys != keys);
+            if command != "none" {
+                keybindings.bindings.push(Keybinding {
+                    keys,
+                    command: command.clone(),
+                });
+            }
+        }
+        (keybindings, errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_sequence, Keybindings};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn key_notation() {
+        let keys = |sequence| parse_sequence(sequence).unwrap();
+        assert_eq!(keys("gg"), vec!["g", "g"]);
+        assert_eq!(keys("]]"), vec!["]", "]"]);
+        assert_eq!(keys("G"), vec!["G"]);
+        assert_eq!(keys("shift+g"), vec!["G"]);
+        assert_eq!(keys("ctrl+shift+p"), vec!["ctrl+P"]);
+        assert_eq!(keys("Ctrl+Alt+Enter"), vec!["ctrl+alt+Enter"]);
+        assert_eq!(keys("shift+space"), vec![" "]);
+        assert_eq!(keys("shift+tab"), vec!["shift+Tab"]);
+        assert_eq!(keys("ctrl+k ctrl+t"), vec!["ctrl+k", "ctrl+t"]);
+        assert_eq!(keys("ctrl++"), vec!["ctrl++"]);
+        assert_eq!(keys("+"), vec!["+"]);
+        assert_eq!(keys("esc"), vec!["Escape"]);
+
+        assert!(parse_sequence("").is_err());
+        assert!(parse_sequence("hyper+j").is_err());
+        assert!(parse_sequence("ctrl+enterr").is_err());
+    }
+
+    #[test]
+    fn labels() {
+        let keybindings = Keybindings::default();
+        assert_eq!(keybindings.labels("command_palette"), vec!["Ctrl+Shift+P"]);
+        assert_eq!(keybindings.labels("find"), vec!["/", "Ctrl+F"]);
+        assert_eq!(keybindings.labels("scroll_top"), vec!["g g"]);
+        assert_eq!(keybindings.labels("scroll_down"), vec!["j", "ArrowDown"]);
+        assert_eq!(keybindings.labels("reload"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn overrides() {
+        let overrides: BTreeMap<String, String> = [
+            ("gg", "none"),
+            ("H", "scroll_top"),
+            ("j", "scroll_up"),
+            ("x", "explode"),
+            ("ctrl+nope", "find"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let (keybindings, errors) = Keybindings::with_overrides(&overrides);
+        let command = |keys: &[&str]| {
+            keybindings
+                .bindings
+                .iter()
+                .find(|b| b.keys == keys)
+                .map(|b| b.command.as_str())
+        };
+        assert_eq!(command(&["g", "g"]), None);
+        assert_eq!(command(&["H"]), Some("scroll_top"));
+        assert_eq!(command(&["j"]), Some("scroll_up"));
+        assert_eq!(command(&["k"]), Some("scroll_up"));
+        assert_eq!(
+            errors,
+            vec![
+                "keybinding \"ctrl+nope\": unknown key \"nope\"",
+                "keybinding \"x\": unknown command \"explode\""
+            ]
+        );
+    }
+}
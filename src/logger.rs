@@ -0,0 +1,170 @@
+//! Logging to stderr and optionally to a file in the state directory
+use log::{LevelFilter, Log, Metadata, Record};
+use simple_logger::SimpleLogger;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// Default level and per module levels
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    pub level: LevelFilter,
+    /// Most specific module first
+    pub modules: Vec<(String, LevelFilter)>,
+}
+
+/// Parses a level or `RUST_LOG` style directives, e.g. `warn,crabix_desktop::editor=trace`.
+/// A module without a level logs everything, like `RUST_LOG=crabix_desktop`.
+pub fn parse_filter(spec: &str) -> Result<LogFilter, String> {
+    let mut filter = LogFilter {
+        level: LevelFilter::Warn,
+        modules: vec![],
+    };
+    for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+        let (module, level) = match directive.split_once('=') {
+            Some((module, level)) => (Some(module.trim()), level.trim()),
+            None if LevelFilter::from_str(directive).is_ok() => (None, directive),
+            None if is_module_path(directive) => (Some(directive), "trace"),
+            None => (None, directive),
+        };
+        let level = LevelFilter::from_str(level).map_err(|_| {
+            format!(
+                "unknown level \"{}\", expected off, error, warn, info, debug or trace",
+                level
+            )
+        })?;
+        match module {
+            Some(module) => filter.modules.push((module.to_string(), level)),
+            None => filter.level = level,
+        }
+    }
+    filter
+        .modules
+        .sort_by_key(|(module, _)| module.len().wrapping_neg());
+    Ok(filter)
+}
+
+// `crate::module`, levels are case insensitive so `Info` isn't a module
+fn is_module_path(s: &str) -> bool {
+    s.split("::")
+        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_'))
+}
+
+struct Logger {
+    stderr: SimpleLogger,
+    file: Option<Mutex<File>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.stderr.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        self.stderr.log(record);
+        if let Some(file) = &self.file {
+            let timestamp = OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_default();
+            let mut file = file.lock().unwrap();
+            let _ = writeln!(
+                file,
+                "{} {:<5} [{}] {}",
+                timestamp,
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            let _ = file.lock().unwrap().flush();
+        }
+    }
+}
+
+/// Installs the global logger, records are appended to `log_file` when it is set
+pub fn init(filter: LogFilter, log_file: Option<&Path>) -> Result<(), String> {
+    let mut stderr = SimpleLogger::new()
+        .with_colors(true)
+        .with_level(filter.level);
+    for (module, level) in filter.modules.iter() {
+        stderr = stderr.with_module_level(module, *level);
+    }
+    let file = match log_file {
+        Some(path) => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            Some(Mutex::new(file))
+        }
+        None => None,
+    };
+
+    let max_level = filter
+        .modules
+        .iter()
+        .map(|(_, level)| *level)
+        .fold(filter.level, Ord::max);
+    log::set_boxed_logger(Box::new(Logger { stderr, file })).map_err(|e| e.to_string())?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_filter;
+    use log::LevelFilter;
+
+    #[test]
+    fn log_filters() {
+        let filter = parse_filter("info").unwrap();
+        assert_eq!(filter.level, LevelFilter::Info);
+        assert!(filter.modules.is_empty());
+        assert_eq!(parse_filter("").unwrap().level, LevelFilter::Warn);
+
+        let filter =
+            parse_filter("error, crabix_desktop=debug,crabix_desktop::editor=TRACE").unwrap();
+        assert_eq!(filter.level, LevelFilter::Error);
+        assert_eq!(
+            filter.modules,
+            vec![
+                ("crabix_desktop::editor".to_string(), LevelFilter::Trace),
+                ("crabix_desktop".to_string(), LevelFilter::Debug),
+            ]
+        );
+
+        assert!(parse_filter("warn,tokio=loud")
+            .unwrap_err()
+            .starts_with("unknown level \"loud\""));
+        assert!(parse_filter("warn,crabix desktop").is_err());
+
+        // Modules without a level, as `RUST_LOG` of other tools often has
+        let filter = parse_filter("info,hyper").unwrap();
+        assert_eq!(filter.level, LevelFilter::Info);
+        assert_eq!(
+            filter.modules,
+            vec![("hyper".to_string(), LevelFilter::Trace)]
+        );
+        let filter = parse_filter("crabix_desktop::editor").unwrap();
+        assert_eq!(filter.level, LevelFilter::Warn);
+        assert_eq!(
+            filter.modules,
+            vec![("crabix_desktop::editor".to_string(), LevelFilter::Trace)]
+        );
+    }
+}
@@ -1,163 +1,964 @@
 #![allow(non_snake_case)]
+mod backlinks;
+mod blocks;
+mod channel;
+mod check;
+mod cli;
+mod command_palette;
+mod commands;
+mod config;
+mod editor;
+mod export;
+mod file_tree;
+mod keybindings;
+mod logger;
 mod markdown_body_css;
 mod markdown_parser;
+mod preview_script;
+mod render_options;
+mod renderer;
+mod scroll_sync;
+mod search;
+mod search_panel;
+mod session;
+#[cfg(test)]
+mod test_util;
+mod title;
+mod toc;
+mod wiki;
+mod workspace;
 
+use backlinks::Backlinks;
+use blocks::BlockId;
+use channel::{FromPreview, ToPreview};
+use clap::Parser;
+use cli::{Cli, Command};
+use command_palette::{CommandPalette, PaletteMode};
+use commands::CommandTarget;
 use dioxus::prelude::*;
+use dioxus_desktop::tao::dpi::{LogicalPosition, LogicalSize};
 use dioxus_desktop::*;
+use editor::{EditorConnection, EditorNotification, EditorRequest};
 use fermi::*;
+use file_tree::FileTree;
+use keybindings::Keybindings;
 use markdown_body_css::*;
-use markdown_it::parser::core::Root;
-use markdown_parser::{MarkdownParser, Spos, SposesExt};
-use simple_logger::SimpleLogger;
-use std::io;
-use std::{env, fs, str};
+use markdown_parser::task_list::toggle_task;
+use markdown_parser::wiki_link::WikiPages;
+use preview_script::*;
+use render_options::{RenderOptions, Theme};
+use renderer::{RenderJob, Rendered, Renderer};
+use search_panel::SearchPanel;
+use session::{Geometry, Session, SharedSession};
+use std::cell::{Cell, RefCell};
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
+use std::ops::RangeInclusive;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+use std::{env, fs, process, str};
+use toc::TocSidebar;
 use tokio::net::UnixListener;
+use wiki::WikiIndex;
+use workspace::Workspace;
+
+type PendingUpdate = (String, u32, Option<u32>, ContentSource);
 
 static MARKDOWN_CONTENT: Atom<String> = |_| "".to_string();
 static SOURCE_FOCUS_LINE: Atom<u32> = |_| 1;
+// Character of the focus line the editor cursor is on, when the editor sends it
+static SOURCE_FOCUS_COLUMN: Atom<Option<u32>> = |_| None;
+static CONTENT_SOURCE: Atom<Option<ContentSource>> = |_| None;
+static TOC_VISIBLE: Atom<bool> = |_| false;
+static RENDER_OPTIONS: Atom<RenderOptions> = |_| RenderOptions::default();
+static WIKI_INDEX: Atom<Option<WikiIndex>> = |_| None;
+static WORKSPACE: Atom<Option<Workspace>> = |_| None;
+static COMMAND_PALETTE: Atom<Option<PaletteMode>> = |_| None;
+static ZOOM: Atom<f64> = |_| 1.0;
+static LIVE_UPDATES_PAUSED: Atom<bool> = |_| false;
+// Latest socket content, focus line and column received while live updates are paused
+static PENDING_UPDATE: Atom<Option<PendingUpdate>> = |_| None;
+// Where the next rendered document is scrolled to, instead of the focus line
+static PENDING_SCROLL: Atom<Option<ScrollTarget>> = |_| None;
+// Newest document rendered in the background
+static RENDERED: Atom<Option<Rendered>> = |_| None;
+// Result of the last command shown in a corner of the window, e.g. where a file is exported
+static STATUS_MESSAGE: Atom<Option<String>> = |_| None;
+
+// How often the workspace directory is rescanned for new and deleted files
+const WORKSPACE_SCAN_INTERVAL: Duration = Duration::from_secs(1);
+// How long command results stay in the corner of the window
+const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(5);
+const ZOOM_STEP: f64 = 1.1;
+// Levels outside of it make the page unusable, also on the next launch
+const ZOOM_RANGE: RangeInclusive<f64> = 0.25..=5.0;
+const SESSION_SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq)]
+enum ContentSource {
+    File(String),
+    // Sent by an editor, optionally naming its buffer
+    Socket(Option<String>),
+}
+
+impl ContentSource {
+    // Opened files are known by their canonical path, like the pages of the wiki index
+    fn file(path: &str) -> ContentSource {
+        let path = Path::new(path)
+            .canonicalize()
+            .map_or_else(|_| path.to_string(), |path| path.display().to_string());
+        ContentSource::File(path)
+    }
+
+    // Shown in the window title
+    fn label(&self) -> String {
+        match self {
+            ContentSource::File(path) => Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.clone()),
+            ContentSource::Socket(Some(buffer)) => format!("buffer {}", buffer),
+            ContentSource::Socket(None) => "socket".to_string(),
+        }
+    }
+}
+
+// Where the document is scrolled to after opening a file
+#[derive(Debug, Clone, PartialEq)]
+enum ScrollTarget {
+    Top,
+    Fragment(String),
+    // Scrolled to by the SOURCE_FOCUS_LINE logic, even when it was focused before
+    Line(u32),
+}
 
 struct AppProps {
     markdown_path: Option<String>,
+    workspace: Option<Workspace>,
+    watch_workspace: bool,
+    keybindings: Keybindings,
+    config: config::Config,
+    session: Session,
+}
+
+// The config file is overridden by environment variables, which are overridden by flags.
+// Returns the config with the problems found in it.
+fn load_config(cli: &Cli) -> (config::Config, Vec<String>) {
+    let mut config = config::Config::load(cli.config_path.as_deref()).unwrap_or_else(|e| {
+        eprintln!("error: Failed to load config {}", e);
+        process::exit(1);
+    });
+    let env_problems = config.apply_env(|var| env::var(var).ok());
+    for warning in &env_problems.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    let mut errors = env_problems.errors;
+    for (key, value) in cli.settings() {
+        if let Err(e) = config.set(key, value) {
+            errors.push(format!("--{}: {}", key.replace('_', "-"), e));
+        }
+    }
+    errors.extend(config.validate());
+    (config, errors)
 }
 
 fn main() {
-    SimpleLogger::new().with_colors(true).init().unwrap();
+    let cli = Cli::parse();
+    if let Some(Command::Completions { shell }) = cli.command {
+        cli::print_completions(shell);
+        return;
+    }
+
+    // Logging is set up by the config, so config errors are printed directly
+    let (config, errors) = load_config(&cli);
+    if let Some(Command::Check { files }) = &cli.command {
+        process::exit(check_files(&config, errors, files));
+    }
+    for error in &errors {
+        eprintln!("error: Invalid config {}", error);
+    }
+    if !errors.is_empty() {
+        process::exit(1);
+    }
+    let log_file = config.log_file_path();
+    if let Err(e) = logger::init(config.log_filter(), log_file.as_deref()) {
+        eprintln!("error: Failed to set up logging {}", e);
+        process::exit(1);
+    }
 
-    let args: Vec<String> = env::args().collect();
+    let result = match cli.command {
+        Some(Command::Render { file, output }) => render(&config, &file, output.as_deref()),
+        Some(Command::Send { file, line }) => send(&config, &file, line),
+        None => {
+            preview(config, &cli.files);
+            Ok(())
+        }
+        _ => unreachable!(),
+    };
+    if let Err(e) = result {
+        log::error!("{}", e);
+        process::exit(1);
+    }
+}
 
-    let mut markdown_path = None;
-    if args.len() >= 2 {
-        markdown_path = Some(args.get(1).unwrap().to_string());
+fn preview(config: config::Config, files: &[String]) {
+    let mut markdown_path = files.first().cloned();
+    let mut workspace = None;
+    // Only a directory is watched, explicitly listed files stay as they are
+    let mut watch_workspace = false;
+    // Several paths are listed files, `Workspace::from_files` rejects directories among them
+    if let [path] = files
+        .iter()
+        .filter(|p| Path::new(p).is_dir())
+        .collect::<Vec<_>>()[..]
+    {
+        if files.len() > 1 {
+            log::error!(
+                "{} is a directory, it can't be opened together with other files",
+                path
+            );
+            process::exit(2);
+        }
+        match Workspace::scan(Path::new(path)) {
+            Ok(ws) => {
+                markdown_path = ws.default_file().map(|p| p.display().to_string());
+                workspace = Some(ws);
+                watch_workspace = true;
+            }
+            Err(e) => {
+                log::error!("Failed to open workspace {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+    } else if files.len() > 1 {
+        let paths: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+        match Workspace::from_files(&paths) {
+            Ok(ws) => {
+                markdown_path = ws.files.first().map(|p| p.display().to_string());
+                workspace = Some(ws);
+            }
+            Err(e) => {
+                log::error!("Failed to open {}: {}", files.join(", "), e);
+                process::exit(1);
+            }
+        }
     }
+    let (keybindings, errors) = Keybindings::with_overrides(&config.keybindings);
+    for error in errors {
+        log::error!("Invalid {}", error);
+    }
+    let configure = ToPreview::Configure {
+        keybindings: &keybindings,
+    }
+    .script();
+    let user_css = match &config.css {
+        Some(path) => fs::read_to_string(path).unwrap_or_else(|e| {
+            log::error!("Failed to read {}: {}", path.display(), e);
+            process::exit(1);
+        }),
+        None => String::new(),
+    };
+    let session = session::session_path()
+        .map(|path| Session::load(&path))
+        .unwrap_or_default();
+    let mut window = WindowBuilder::new().with_title(&config.window_title);
+    // The flag or setting wins over the size the window had last time
+    if let Some(geometry) = config.geometry().or(session.window) {
+        window = window.with_inner_size(LogicalSize::new(geometry.width, geometry.height));
+        if let Some((x, y)) = geometry.position {
+            window = window.with_position(LogicalPosition::new(x, y));
+        }
+    }
+
     dioxus_desktop::launch_with_props(
         app,
-        AppProps { markdown_path },
+        AppProps {
+            markdown_path,
+            workspace,
+            watch_workspace,
+            keybindings,
+            config,
+            session,
+        },
         Config::default()
-            .with_custom_head(format!("<style>{}</style>", MARKDOWN_BODY_CSS))
-            .with_window(WindowBuilder::new().with_title("Crabix Desktop")),
+            .with_custom_head(format!(
+                "<style>{}</style><style>{}</style><script>{}{}</script>",
+                MARKDOWN_BODY_CSS, user_css, PREVIEW_SCRIPT, configure
+            ))
+            .with_window(window),
     );
 }
 
+// `-` is the standard input
+fn read_source(file: &str) -> Result<String, String> {
+    let result = if file == "-" {
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content).map(|_| content)
+    } else {
+        fs::read_to_string(file)
+    };
+    result.map_err(|e| format!("Failed to read {}: {}", file, e))
+}
+
+fn render(config: &config::Config, file: &str, output: Option<&Path>) -> Result<(), String> {
+    let content = read_source(file)?;
+    let options = config.render_options();
+    let title = Path::new(file)
+        .file_stem()
+        .filter(|_| file != "-")
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| config.window_title.clone());
+    let html = export::html_document(&content, &options, &title);
+    match output {
+        Some(path) => {
+            fs::write(path, html).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+        }
+        None => io::stdout()
+            .write_all(html.as_bytes())
+            .map_err(|e| e.to_string()),
+    }
+}
+
+// Sends the file like the editor plugins do, `<line> <content>`
+fn send(config: &config::Config, file: &str, line: u32) -> Result<(), String> {
+    let content = read_source(file)?;
+    let socket_path = &config.socket_path;
+    let mut stream = UnixStream::connect(socket_path).map_err(|e| {
+        format!(
+            "Failed to connect to {}: {}, is crabix-desktop running?",
+            socket_path.display(),
+            e
+        )
+    })?;
+    stream
+        .write_all(format!("{} {}", line, content).as_bytes())
+        .and_then(|_| stream.shutdown(Shutdown::Write))
+        .map_err(|e| format!("Failed to send {}: {}", file, e))
+}
+
+// Prints the config and document problems, returns the exit code
+fn check_files(config: &config::Config, mut errors: Vec<String>, files: &[String]) -> i32 {
+    let mut problems = 0;
+    errors.extend(Keybindings::with_overrides(&config.keybindings).1);
+    for error in errors {
+        println!("config: {}", error);
+        problems += 1;
+    }
+
+    let index = config
+        .wiki_root
+        .as_ref()
+        .and_then(|root| WikiIndex::build(root).ok());
+    let parser = &mut RenderOptions::default().parser();
+    if let Some(index) = &index {
+        parser.set_wiki_pages(index.pages.clone());
+    }
+    for file in files {
+        let content = match read_source(file) {
+            Ok(content) => content,
+            Err(e) => {
+                println!("{}", e);
+                problems += 1;
+                continue;
+            }
+        };
+        for problem in check::check_document(parser, &content, index.is_some()) {
+            println!("{}:{}: {}", file, problem.line, problem.message);
+            problems += 1;
+        }
+    }
+    if problems > 0 {
+        println!("{} problem(s) found", problems);
+        1
+    } else {
+        println!("No problems found");
+        0
+    }
+}
+
 #[inline_props]
 pub fn Markdown(cx: Scope<'a>) -> Element {
     let con = use_read(cx, MARKDOWN_CONTENT);
     let source_line = use_read(cx, SOURCE_FOCUS_LINE);
+    let source_column = *use_read(cx, SOURCE_FOCUS_COLUMN);
+    let options = use_read(cx, RENDER_OPTIONS);
+    let wiki_pages = use_read(cx, WIKI_INDEX).as_ref().map(|index| &index.pages);
+    let (rendered, current) = use_rendered(cx, con, options, wiki_pages);
+    let options = &rendered.options;
+    let headings = &rendered.headings;
+    use_document_options(cx, options, rendered.title.clone());
 
-    log::trace!("Parsing markdown");
-    let parser = &mut MarkdownParser::new();
-    let ast = parser.parse(con);
-    let root_node = ast.cast::<Root>().unwrap();
-    let spos_ext = root_node.ext.get::<SposesExt>().unwrap();
-    log::trace!("Markdown parsed");
-
-    let ss = Spos::find(*source_line, &spos_ext.sposes);
+    let ss = rendered.sposes.find(*source_line);
     let cs = *source_line;
     log::trace!("find spos result: {:?}", ss);
 
     let eval = dioxus_desktop::use_eval(cx).clone();
+    let session = use_context::<SharedSession>(cx).unwrap();
+    let sync = use_context::<config::Config>(cx).unwrap().scroll_sync();
+    let source = use_read(cx, CONTENT_SOURCE);
+    // Only the block at the editor cursor is highlighted, not the top of an opened file
+    let highlight = matches!(source, Some(ContentSource::Socket(_)));
+    // Typing on the same line of the same document doesn't scroll, the reader may have
+    // scrolled away. Moving along the line only reveals the word at the cursor when it is out of view.
+    let scrolled_line = cx.use_hook(|| RefCell::new(None));
+    let pending_scroll = (*use_atom_root(cx).read(PENDING_SCROLL)).clone();
+    let setPendingScroll = use_set(cx, PENDING_SCROLL);
 
-    if let Some(s) = ss {
-        // Should be removed https://github.com/DioxusLabs/dioxus/issues/804
-        cx.push_future(async move {
-            let template = format!(
-                r#"
-            function calcOffset(height, spos_start, spos_end, current_pos) {{
-                let steps = spos_end - spos_start;
-                if (steps == 0) return 0;
-                let step_value = height / steps;
-                return (current_pos - spos_start) * step_value
-            }}
-
-            function scrollToElement(element) {{
-              const rect = element.getBoundingClientRect();
-              const elementTop = rect.top + window.pageYOffset;
-              const elementMiddle = elementTop - (window.innerHeight / 2);
-              const offset = calcOffset(rect.height, {spos_start}, {spos_end}, {current_pos})
-
-              window.scrollTo({{
-                  top: elementMiddle + offset,
-                  left: 0,
-                  behavior: 'smooth'
-              }});
-            }}
-            setTimeout(function(){{
-                const element = document.querySelector(`[data-spos='{spos_start}-{spos_end}']`);
-                scrollToElement(element)
-            }}, 100);
-
-            "#,
-                spos_start = s.start_line,
-                spos_end = s.end_line,
-                current_pos = cs
+    // Generation and blocks the preview shows, patches are based on them
+    let shown = &*cx.use_hook(|| RefCell::new(None::<(u64, Vec<BlockId>)>));
+
+    let mut messages = vec![];
+    if rendered.generation != 0
+        && shown.borrow().as_ref().map(|(generation, _)| *generation) != Some(rendered.generation)
+    {
+        let mut shown = shown.borrow_mut();
+        let patch = ToPreview::Patch {
+            base: shown.as_ref().map(|(generation, _)| *generation),
+            generation: rendered.generation,
+            blocks: blocks::diff(shown.as_ref().map_or(&[], |(_, ids)| ids), &rendered.blocks),
+        };
+        messages.push(patch.script());
+        *shown = Some((
+            rendered.generation,
+            rendered.blocks.iter().map(|block| block.id).collect(),
+        ));
+    }
+    // Scrolling waits for the document at the cursor line, blocks of stale content are elsewhere
+    if current {
+        let scrolled = scrolled_line.replace(Some((source.clone(), cs, source_column)));
+        let scroll = matches!(pending_scroll, Some(ScrollTarget::Line(_)))
+            || scrolled.as_ref().map(|(source, line, _)| (source, *line)) != Some((source, cs));
+        let reveal = !scroll && scrolled.map(|(_, _, column)| column) != Some(source_column);
+        if pending_scroll.is_some() {
+            setPendingScroll(None);
+        }
+        if let Some(ScrollTarget::Top) = pending_scroll {
+            // Files are opened where they were left last time
+            let offset = match source {
+                Some(ContentSource::File(path)) => session.borrow().scroll_offset(path),
+                _ => None,
+            };
+            let offset = offset.unwrap_or(0.0);
+            messages.push(ToPreview::ScrollToOffset { offset }.script());
+        } else if let Some(ScrollTarget::Fragment(id)) = &pending_scroll {
+            messages.push(ToPreview::ScrollToFragment { id }.script());
+        } else if let Some(spos) = ss.filter(|_| scroll || reveal) {
+            messages.push(
+                ToPreview::ScrollTo {
+                    spos,
+                    line: cs,
+                    column: source_column,
+                    reveal_only: reveal,
+                    block_fraction: sync.block_fraction(spos.start_line, spos.end_line, cs),
+                    anchor: sync.anchor.fraction(),
+                    smooth: sync.smooth,
+                }
+                .script(),
             );
-            eval(template);
+        }
+        if highlight {
+            let highlight = ToPreview::Highlight {
+                spos: ss,
+                line: cs,
+                column: source_column,
+            };
+            messages.push(highlight.script());
+        }
+    }
+    if !messages.is_empty() {
+        // Evaluating while rendering isn't supported https://github.com/DioxusLabs/dioxus/issues/804
+        cx.push_future(async move {
+            for script in messages {
+                eval(script);
+            }
         });
     }
 
-    let html = ast.render();
+    let toc_visible = *use_read(cx, TOC_VISIBLE);
+    let theme = options.theme.class();
+    let focus = options.focus_highlight.class();
+    let style = options.style();
+    let with_toc = if toc_visible { "with-toc" } else { "" };
+
+    let toggle = use_task_toggle(cx);
+    let open_file = &*cx.bump().alloc(use_open_file(cx));
+    let run_command = &*cx.bump().alloc(use_run_command(cx));
+    let paused = *use_read(cx, LIVE_UPDATES_PAUSED);
+    let status = use_read(cx, STATUS_MESSAGE).clone();
+    let setStatus = use_set(cx, STATUS_MESSAGE);
+    // A newer message is shown for the full time again
+    use_future(cx, (&status,), |(status,)| {
+        let setStatus = setStatus.clone();
+        async move {
+            if status.is_some() {
+                tokio::time::sleep(STATUS_MESSAGE_DURATION).await;
+                setStatus(None);
+            }
+        }
+    });
+    let with_files = if use_read(cx, WORKSPACE).is_some() {
+        "with-files"
+    } else {
+        ""
+    };
+    let file_path = match source {
+        Some(ContentSource::File(path)) => Some(path.clone()),
+        _ => None,
+    };
+    let scrolled_path = file_path.clone();
     cx.render(rsx! {
         div {
-            class: "markdown-body",
-            dangerous_inner_html: "{html}"
+            class: "crabix-layout {theme} {focus} {with_files} {with_toc}",
+            style: "{style}",
+            FileTree {
+                on_open: move |path: String| open_file(&path, ScrollTarget::Top)
+            }
+            SearchPanel {
+                on_open: move |(path, line): (String, u32)| open_file(&path, ScrollTarget::Line(line))
+            }
+            TocSidebar {
+                headings: headings.clone(),
+                source_line: cs
+            }
+            // Filled by the preview script from patch messages
+            div {
+                class: "markdown-body"
+            }
+            if let Some(path) = file_path {
+                rsx! { Backlinks { path: path } }
+            }
+            if paused {
+                rsx! {
+                    div {
+                        class: "live-updates-paused",
+                        title: "Run \"Pause or resume live updates\" to resume",
+                        "Live updates paused"
+                    }
+                }
+            }
+            if let Some(message) = status {
+                rsx! {
+                    div {
+                        class: "status-message",
+                        onclick: move |_| setStatus(None),
+                        "{message}"
+                    }
+                }
+            }
+            CommandPalette {
+                on_command: move |name: &'static str| run_command(name),
+                on_open: move |path: String| open_file(&path, ScrollTarget::Top)
+            }
+        }
+        input {
+            id: "crabix-channel",
+            r#type: "hidden",
+            oninput: move |evt| match FromPreview::parse(&evt.value) {
+                Ok(FromPreview::Ready { version }) if version != channel::VERSION => {
+                    log::error!("Preview script version {} doesn't match {}", version, channel::VERSION)
+                }
+                Ok(FromPreview::Ready { .. }) => log::debug!("Preview script loaded"),
+                Ok(FromPreview::ToggleTask { line }) => toggle(line),
+                Ok(FromPreview::OpenFile { path, fragment }) => {
+                    open_file(&path, fragment.map_or(ScrollTarget::Top, ScrollTarget::Fragment))
+                }
+                Ok(FromPreview::RunCommand { name }) => run_command(&name),
+                Ok(FromPreview::Scroll { offset }) => {
+                    // Only files are scrolled back to where they were
+                    if let Some(path) = &scrolled_path {
+                        session.borrow_mut().set_scroll_offset(path, offset);
+                    }
+                }
+                Ok(FromPreview::Resync) => {
+                    shown.replace(None);
+                    cx.needs_update();
+                }
+                Err(e) => log::error!("Invalid message from the preview script: {}", e),
+            }
         }
     })
 }
 
-fn spawn_unix_socket_listener(cx: &Scope<AppProps>) {
+// Submits the document to the background renderer when it changed.
+// Returns the newest rendered document and whether it is the submitted one.
+fn use_rendered<'a>(
+    cx: &'a ScopeState,
+    content: &str,
+    options: &RenderOptions,
+    wiki_pages: Option<&WikiPages>,
+) -> (&'a Rendered, bool) {
+    let renderer = use_context::<Rc<Renderer>>(cx).unwrap();
+    let rendered = use_read(cx, RENDERED);
+    let empty = cx.use_hook(Rendered::default);
+    let submitted = cx.use_hook(|| RefCell::new(None::<RenderJob>));
+
+    let mut submitted = submitted.borrow_mut();
+    let changed = submitted.as_ref().is_none_or(|job| {
+        job.content != content || job.options != *options || job.wiki_pages.as_ref() != wiki_pages
+    });
+    if changed {
+        let job = RenderJob {
+            generation: submitted.as_ref().map_or(1, |job| job.generation + 1),
+            content: content.to_string(),
+            options: options.clone(),
+            wiki_pages: wiki_pages.cloned(),
+        };
+        renderer.submit(job.clone());
+        *submitted = Some(job);
+    }
+    match rendered {
+        Some(rendered) => (
+            rendered,
+            submitted.as_ref().map(|job| job.generation) == Some(rendered.generation),
+        ),
+        None => (empty, false),
+    }
+}
+
+// Applies document settings that live outside of the rendered markdown
+fn use_document_options(cx: &ScopeState, options: &RenderOptions, title: Option<String>) {
+    let window = dioxus_desktop::use_window(cx);
+    let config = use_context::<config::Config>(cx).unwrap();
+    let setTocVisible = use_set(cx, TOC_VISIBLE);
+    // The sidebar is only toggled when the document asks for another state,
+    // so re-rendering doesn't undo what the user did
+    let applied_toc = cx.use_hook(|| Cell::new(None));
+
+    let source = use_read(cx, CONTENT_SOURCE)
+        .as_ref()
+        .map(ContentSource::label);
+    window.set_title(&title::window_title(
+        title.as_deref(),
+        source.as_deref(),
+        &config.window_title,
+    ));
+    if applied_toc.get() != Some(options.toc) {
+        applied_toc.set(Some(options.toc));
+        setTocVisible(options.toc);
+    }
+}
+
+// Flips the task list checkbox at the given source line
+// and propagates the change back to where the content came from
+fn use_task_toggle(cx: &ScopeState) -> impl Fn(u32) + '_ {
+    let content = use_read(cx, MARKDOWN_CONTENT);
+    let source = use_read(cx, CONTENT_SOURCE);
+    let setContent = use_set(cx, MARKDOWN_CONTENT);
+    let editor = use_context::<EditorConnection>(cx);
+
+    move |line: u32| {
+        let Some((new_content, checked)) = toggle_task(content, line) else {
+            log::warn!("No task list item at line {}", line);
+            return;
+        };
+        match source {
+            Some(ContentSource::File(path)) => {
+                if let Err(e) = fs::write(path, &new_content) {
+                    log::error!("Failed to write {}: {}", path, e);
+                    return;
+                }
+            }
+            // The editor owns the content, it changes once the editor has been told
+            Some(ContentSource::Socket(_)) => {
+                let Some(editor) = editor else {
+                    log::warn!("No editor connected, task at line {} is unchanged", line);
+                    return;
+                };
+                let editor = editor.clone();
+                let setContent = setContent.clone();
+                cx.spawn(async move {
+                    let notification = EditorNotification::ToggleTask { line, checked };
+                    if editor::notify(&editor, notification).await {
+                        setContent(new_content);
+                    }
+                });
+                return;
+            }
+            None => return,
+        }
+        setContent(new_content);
+    }
+}
+
+// Sends messages to the preview script
+fn use_preview(cx: &ScopeState) -> impl Fn(ToPreview) + '_ {
+    let eval = dioxus_desktop::use_eval(cx);
+    move |message: ToPreview| {
+        eval(message.script());
+    }
+}
+
+// Runs commands from keybindings and the command palette
+fn use_run_command(cx: &ScopeState) -> impl Fn(&str) + '_ {
+    let window = dioxus_desktop::use_window(cx);
+    let preview = use_preview(cx);
+    let content = use_read(cx, MARKDOWN_CONTENT);
+    let source = use_read(cx, CONTENT_SOURCE);
+    let options = use_read(cx, RENDER_OPTIONS);
+    let toc_visible = *use_read(cx, TOC_VISIBLE);
+    let zoom = *use_read(cx, ZOOM);
+    let paused = *use_read(cx, LIVE_UPDATES_PAUSED);
+    let pending = use_read(cx, PENDING_UPDATE);
+    let setContent = use_set(cx, MARKDOWN_CONTENT);
+    let setFocusLine = use_set(cx, SOURCE_FOCUS_LINE);
+    let setFocusColumn = use_set(cx, SOURCE_FOCUS_COLUMN);
+    let setSource = use_set(cx, CONTENT_SOURCE);
+    let setOptions = use_set(cx, RENDER_OPTIONS);
+    let setTocVisible = use_set(cx, TOC_VISIBLE);
+    let setZoom = use_set(cx, ZOOM);
+    let setPaused = use_set(cx, LIVE_UPDATES_PAUSED);
+    let setPending = use_set(cx, PENDING_UPDATE);
+    let setPalette = use_set(cx, COMMAND_PALETTE);
+    let setStatus = use_set(cx, STATUS_MESSAGE);
+    let session = use_context::<SharedSession>(cx).unwrap();
+
+    let set_zoom = move |level: f64| set_zoom(window, setZoom, level);
+    move |name: &str| {
+        let Some(command) = commands::find(name) else {
+            log::warn!("Unknown command: {}", name);
+            return;
+        };
+        if command.target == CommandTarget::Preview {
+            preview(ToPreview::RunCommand { name: command.name });
+            return;
+        }
+        match command.name {
+            "command_palette" | "open_file" => {
+                let mode = match command.name {
+                    "open_file" => PaletteMode::OpenFile,
+                    _ => PaletteMode::Commands,
+                };
+                setPalette(Some(mode));
+                preview(ToPreview::FocusCommandPalette);
+            }
+            "reload" => match source {
+                Some(ContentSource::File(path)) => match fs::read_to_string(path) {
+                    Ok(content) => setContent(content),
+                    Err(e) => log::error!("Failed to reload {}: {}", path, e),
+                },
+                _ => log::warn!("Only files can be reloaded"),
+            },
+            "toggle_live_updates" => {
+                if paused {
+                    if let Some((content, line, column, source)) = pending.clone() {
+                        setContent(content);
+                        setFocusLine(line);
+                        setFocusColumn(column);
+                        setSource(Some(source));
+                        setPending(None);
+                    }
+                }
+                setPaused(!paused);
+            }
+            "toggle_toc" => setTocVisible(!toc_visible),
+            "toggle_theme" => {
+                let mut options = options.clone();
+                options.theme = match options.theme {
+                    Theme::Light => Theme::Dark,
+                    Theme::Dark => Theme::Light,
+                };
+                setOptions(options);
+            }
+            "zoom_in" => set_zoom(zoom * ZOOM_STEP),
+            "zoom_out" => set_zoom(zoom / ZOOM_STEP),
+            "reset_zoom" => set_zoom(1.0),
+            "export_html" => {
+                let (source, title) = match source {
+                    Some(ContentSource::File(path)) => {
+                        let path = Path::new(path);
+                        let title = path
+                            .file_stem()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        (path.to_path_buf(), title)
+                    }
+                    _ => (
+                        env::temp_dir().join("crabix-export.md"),
+                        "Crabix Desktop".to_string(),
+                    ),
+                };
+                let path = export::export_path(&source);
+                let message =
+                    match fs::write(&path, export::html_document(content, options, &title)) {
+                        Ok(()) => {
+                            log::info!("Exported {}", path.display());
+                            format!("Exported {}", path.display())
+                        }
+                        Err(e) => {
+                            log::error!("Failed to export {}: {}", path.display(), e);
+                            format!("Failed to export {}: {}", path.display(), e)
+                        }
+                    };
+                setStatus(Some(message));
+            }
+            "close" => {
+                save_session(session, window, zoom);
+                window.close();
+            }
+            _ => log::warn!("Command {} is not implemented", command.name),
+        }
+    }
+}
+
+// Opens a markdown file in place of the current document
+fn use_open_file(cx: &ScopeState) -> impl Fn(&str, ScrollTarget) + '_ {
+    let setContent = use_set(cx, MARKDOWN_CONTENT);
+    let setFocusLine = use_set(cx, SOURCE_FOCUS_LINE);
+    let setFocusColumn = use_set(cx, SOURCE_FOCUS_COLUMN);
+    let setSource = use_set(cx, CONTENT_SOURCE);
+    let setWikiIndex = use_set(cx, WIKI_INDEX);
+    let setPendingScroll = use_set(cx, PENDING_SCROLL);
+    let index = use_read(cx, WIKI_INDEX);
+
+    move |path: &str, target: ScrollTarget| {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                log::error!("Failed to open {}: {}", path, e);
+                return;
+            }
+        };
+        // Backlinks of the opened page may have changed since the index was built
+        if let Some(index) = index {
+            spawn_wiki_index_build(cx, index.root.clone(), setWikiIndex.clone());
+        }
+        setContent(content);
+        setSource(Some(ContentSource::file(path)));
+        setFocusColumn(None);
+
+        setFocusLine(match target {
+            ScrollTarget::Line(line) => line,
+            _ => 1,
+        });
+        setPendingScroll(Some(target));
+    }
+}
+
+fn spawn_unix_socket_listener(cx: &Scope<AppProps>, editor: EditorConnection) {
     cx.spawn({
         let setContent = use_set(cx, MARKDOWN_CONTENT).clone();
         let setFocusLine = use_set(cx, SOURCE_FOCUS_LINE).clone();
-
+        let setFocusColumn = use_set(cx, SOURCE_FOCUS_COLUMN).clone();
+        let setSource = use_set(cx, CONTENT_SOURCE).clone();
+        let setWikiIndex = use_set(cx, WIKI_INDEX).clone();
+        let setWorkspace = use_set(cx, WORKSPACE).clone();
+        let setPending = use_set(cx, PENDING_UPDATE).clone();
+        let setPendingScroll = use_set(cx, PENDING_SCROLL).clone();
+        let setZoom = use_set(cx, ZOOM).clone();
+        let setOptions = use_set(cx, RENDER_OPTIONS).clone();
+        let atoms = use_atom_root(cx).clone();
+        let window = dioxus_desktop::use_window(cx).clone();
         if let Some(markdown_path) = &cx.props.markdown_path {
-            let file_content: String = fs::read_to_string(markdown_path).unwrap().parse().unwrap();
-            setContent(file_content.clone());
+            match fs::read_to_string(markdown_path) {
+                Ok(file_content) => {
+                    setContent(file_content);
+                    setSource(Some(ContentSource::file(markdown_path)));
+                    setPendingScroll(Some(ScrollTarget::Top));
+                }
+                Err(e) => log::error!("Failed to open {}: {}", markdown_path, e),
+            }
         }
+        setWorkspace(cx.props.workspace.clone());
+        // Notes in the workspace link to each other unless a wiki root is configured
+        let wiki_root = cx
+            .props
+            .config
+            .wiki_root
+            .clone()
+            .or_else(|| Some(cx.props.workspace.as_ref()?.root.clone()));
+        if let Some(root) = wiki_root {
+            spawn_wiki_index_build(cx, root, setWikiIndex.clone());
+        }
+
+        let handle_update = {
+            let atoms = atoms.clone();
+            move |update: editor::Update| {
+                log::trace!("Source position: {}:{:?}", update.line, update.column);
+                let source = ContentSource::Socket(update.buffer.map(str::to_string));
+                let markdown = update.content.to_string();
+                if *atoms.read(LIVE_UPDATES_PAUSED) {
+                    log::trace!("Live updates paused");
+                    setPending(Some((markdown, update.line, update.column, source)));
+                } else {
+                    setContent(markdown);
+                    setFocusLine(update.line);
+                    setFocusColumn(update.column);
+                    setSource(Some(source));
+                }
+            }
+        };
+        let handle_request = {
+            let atoms = atoms.clone();
+            move |request: EditorRequest| match request {
+                EditorRequest::Zoom { level } if level <= 0.0 => {
+                    log::warn!("Invalid zoom level: {}", level);
+                }
+                EditorRequest::Zoom { level } => set_zoom(&window, &setZoom, level),
+                EditorRequest::Set { key, value } => {
+                    let mut options = (*atoms.read(RENDER_OPTIONS)).clone();
+                    match options.set(&key, &value) {
+                        Ok(()) => setOptions(options),
+                        Err(e) => log::warn!("Invalid {}: {}", key, e),
+                    }
+                }
+            }
+        };
 
         // TODO Need to figure out max packet size
         let mut msg = vec![0; 1_000_000];
-        let _ = fs::remove_file("/tmp/crabix");
-        let listener = UnixListener::bind("/tmp/crabix").unwrap();
+        let socket_path = &cx.props.config.socket_path;
+        let _ = fs::remove_file(socket_path);
+        let listener = UnixListener::bind(socket_path).unwrap_or_else(|e| {
+            log::error!("Failed to listen on {}: {}", socket_path.display(), e);
+            std::process::exit(1);
+        });
         let mut content = vec![];
         let mut total_bytes = 0;
         async move {
             loop {
                 match listener.accept().await {
-                    Ok((stream, _addr)) => loop {
+                    Ok((stream, _addr)) => {
                         log::trace!("Client connection accepted");
-                        let res = stream.readable().await;
-                        if res.is_ok() {
-                            match stream.try_read(&mut msg) {
-                                Ok(0) => {
-                                    let msgs = String::from(
-                                        str::from_utf8(&content[..total_bytes]).unwrap(),
-                                    );
-                                    let source_line_number_len =
-                                        msgs.chars().take_while(|c| c.is_digit(10)).count();
-                                    let (number, contentt) = msgs.split_at(source_line_number_len);
-                                    log::trace!("Source line number: {:?}", number);
-
-                                    setContent(contentt[1..].to_string());
-                                    setFocusLine(number.parse::<u32>().unwrap());
-                                    log::trace!("Connection closed");
-                                    content.clear();
-                                    total_bytes = 0;
-                                    break;
-                                }
-                                Ok(n) => {
-                                    log::trace!("Read {:?} bytes", n);
-                                    total_bytes += n;
-                                    content.extend(&msg[..n]);
-                                }
-                                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                                    continue;
-                                }
-                                Err(e) => {
-                                    log::error!("{}", e);
-                                    return ();
+                        let (stream, write_half) = stream.into_split();
+                        // Keep the connection open to be able to notify the editor about edits
+                        *editor.lock().await = Some(write_half);
+                        loop {
+                            let res = stream.readable().await;
+                            if res.is_ok() {
+                                match stream.try_read(&mut msg) {
+                                    Ok(0) => {
+                                        let msgs = String::from_utf8_lossy(&content[..total_bytes]);
+                                        match editor::parse_request(&msgs) {
+                                            Some(Ok(request)) => handle_request(request),
+                                            Some(Err(e)) => log::warn!("Invalid request: {}", e),
+                                            None => match editor::parse_update(&msgs) {
+                                                Some(update) => handle_update(update),
+                                                None => log::warn!("Invalid message, expected LINE[@BUFFER] CONTENT"),
+                                            },
+                                        }
+                                        log::trace!("Connection closed");
+                                        content.clear();
+                                        total_bytes = 0;
+                                        break;
+                                    }
+                                    Ok(n) => {
+                                        log::trace!("Read {:?} bytes", n);
+                                        total_bytes += n;
+                                        content.extend(&msg[..n]);
+                                    }
+                                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                                        continue;
+                                    }
+                                    Err(e) => {
+                                        log::error!("{}", e);
+                                        return;
+                                    }
                                 }
                             }
                         }
-                    },
+                    }
                     Err(e) => {
                         log::error!("{}", e);
                     }
@@ -167,10 +968,147 @@ fn spawn_unix_socket_listener(cx: &Scope<AppProps>) {
     });
 }
 
+// Rescans the workspace directory so new and deleted files show up in the file tree
+fn spawn_workspace_watcher(cx: &Scope<AppProps>, root: &Path) {
+    let setWorkspace = use_set(cx, WORKSPACE).clone();
+    let root = root.to_path_buf();
+    cx.spawn(async move {
+        let mut files = vec![];
+        loop {
+            tokio::time::sleep(WORKSPACE_SCAN_INTERVAL).await;
+            // Reading the directories would block the UI
+            let scan = tokio::task::spawn_blocking({
+                let root = root.clone();
+                move || Workspace::scan(&root)
+            });
+            let Ok(scan) = scan.await else {
+                log::error!("Workspace scan of {} failed", root.display());
+                continue;
+            };
+            match scan {
+                Ok(workspace) if workspace.files != files => {
+                    log::trace!("Workspace changed");
+                    files = workspace.files.clone();
+                    setWorkspace(Some(workspace));
+                }
+                Ok(_) => {}
+                Err(e) => log::error!("Failed to scan {}: {}", root.display(), e),
+            }
+        }
+    });
+}
+
+// Window size and position in logical pixels, as given to the `WindowBuilder`
+fn window_geometry(window: &DesktopContext) -> Geometry {
+    let scale = window.scale_factor();
+    let size: LogicalSize<u32> = window.inner_size().to_logical(scale);
+    let position = window
+        .outer_position()
+        .ok()
+        .map(|position| position.to_logical::<i32>(scale));
+    Geometry {
+        width: size.width,
+        height: size.height,
+        position: position.map(|position| (position.x, position.y)),
+    }
+}
+
+// Every zoom change goes through here to keep the level in ZOOM_RANGE
+fn set_zoom(window: &DesktopContext, setZoom: &Rc<dyn Fn(f64)>, level: f64) {
+    let level = level.clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+    window.set_zoom_level(level);
+    setZoom(level);
+}
+
+// Reading every page of the wiki would block the UI
+fn spawn_wiki_index_build(
+    cx: &ScopeState,
+    root: PathBuf,
+    setWikiIndex: Rc<dyn Fn(Option<WikiIndex>)>,
+) {
+    cx.spawn(async move {
+        let build = tokio::task::spawn_blocking({
+            let root = root.clone();
+            move || WikiIndex::build(&root)
+        });
+        match build.await {
+            Ok(Ok(index)) => setWikiIndex(Some(index)),
+            Ok(Err(e)) => log::error!("Failed to index {}: {}", root.display(), e),
+            Err(e) => log::error!("Indexing {} failed: {}", root.display(), e),
+        }
+    });
+}
+
+fn save_session(session: &RefCell<Session>, window: &DesktopContext, zoom: f64) {
+    let mut session = session.borrow_mut();
+    session.window = Some(window_geometry(window));
+    session.zoom = zoom;
+    if let Some(path) = session::session_path() {
+        session.save(&path);
+    }
+}
+
+// dioxus-desktop doesn't tell when the window is closed by the window manager,
+// so the session is saved whenever it changes
+fn spawn_session_saver(
+    cx: &Scope<AppProps>,
+    session: SharedSession,
+    window: DesktopContext,
+    atoms: Rc<AtomRoot>,
+) {
+    cx.spawn(async move {
+        let mut saved = session.borrow().clone();
+        loop {
+            tokio::time::sleep(SESSION_SAVE_INTERVAL).await;
+            let zoom = *atoms.read(ZOOM);
+            let mut current = session.borrow().clone();
+            current.window = Some(window_geometry(&window));
+            current.zoom = zoom;
+            if current != saved {
+                save_session(&session, &window, zoom);
+                saved = session.borrow().clone();
+            }
+        }
+    });
+}
+
 fn app(cx: Scope<AppProps>) -> Element {
     log::trace!("Run root element!");
     use_init_atom_root(cx);
-    spawn_unix_socket_listener(&cx);
+    let editor = cx.use_hook(|| cx.provide_context(EditorConnection::default()));
+    cx.use_hook(|| cx.provide_context(cx.props.keybindings.clone()));
+    cx.use_hook(|| cx.provide_context(cx.props.config.clone()));
+    let session: &SharedSession =
+        cx.use_hook(|| cx.provide_context(Rc::new(RefCell::new(cx.props.session.clone()))));
+    let window = dioxus_desktop::use_window(cx);
+    let setOptions = use_set(cx, RENDER_OPTIONS);
+    let setZoom = use_set(cx, ZOOM);
+    let atoms = use_atom_root(cx);
+    let setRendered = use_set(cx, RENDERED);
+    cx.use_hook(|| setOptions(cx.props.config.render_options()));
+    cx.use_hook(|| {
+        let (renderer, mut results) = Renderer::new();
+        let setRendered = setRendered.clone();
+        cx.spawn(async move {
+            while let Some(rendered) = results.recv().await {
+                setRendered(Some(rendered));
+            }
+        });
+        cx.provide_context(Rc::new(renderer))
+    });
+    spawn_unix_socket_listener(&cx, editor.clone());
+    cx.use_hook(|| {
+        set_zoom(window, setZoom, session.borrow().zoom);
+        spawn_session_saver(&cx, session.clone(), window.clone(), atoms.clone());
+    });
+    if let Some(workspace) = cx
+        .props
+        .workspace
+        .as_ref()
+        .filter(|_| cx.props.watch_workspace)
+    {
+        cx.use_hook(|| spawn_workspace_watcher(&cx, &workspace.root));
+    }
 
     cx.render(rsx! {
             Markdown {}
@@ -1,4 +1,4 @@
-pub const MARKDOWN_BODY_CSS: &str = r#"
+pub const MARKDOWN_BODY_CSS: &str = r##"
 .margin-tb-zero,
 .markdown-body ol ol,
 .markdown-body ul ol,
@@ -12,12 +12,15 @@ pub const MARKDOWN_BODY_CSS: &str = r#"
   margin-bottom: 0;
 }
 .markdown-body {
-  font-family: "Helvetica Neue", Helvetica, "Segoe UI", Arial, freesans, sans-serif, "Apple Color Emoji", "Segoe UI Emoji", "Segoe UI Symbol";
-  font-size: 16px;
+  font-family: var(--crabix-font, "Helvetica Neue", Helvetica, "Segoe UI", Arial, freesans, sans-serif, "Apple Color Emoji", "Segoe UI Emoji", "Segoe UI Symbol");
+  font-size: var(--crabix-font-size, 16px);
   color: #333;
   line-height: 1.6;
   word-wrap: break-word;
-  padding: 45px;
+  max-width: var(--crabix-content-width, none);
+  margin-right: auto;
+  margin-left: auto;
+  padding: var(--crabix-content-padding, 45px);
   background: #fff;
   border: 1px solid #ddd;
   -webkit-border-radius: 0 0 3px 3px;
@@ -45,6 +48,40 @@ pub const MARKDOWN_BODY_CSS: &str = r#"
   font-weight: bold;
   line-height: 1.4;
 }
+.markdown-body h1,
+.markdown-body h2,
+.markdown-body h3,
+.markdown-body h4,
+.markdown-body h5,
+.markdown-body h6 {
+  position: relative;
+}
+.markdown-body .anchor {
+  position: absolute;
+  top: 0;
+  left: 0;
+  display: block;
+  padding-right: 6px;
+  padding-left: 30px;
+  margin-left: -30px;
+  color: #4078c0;
+  font-weight: normal;
+}
+.markdown-body .anchor:focus {
+  outline: none;
+}
+.markdown-body .anchor:before {
+  content: "#";
+  visibility: hidden;
+}
+.markdown-body h1:hover .anchor:before,
+.markdown-body h2:hover .anchor:before,
+.markdown-body h3:hover .anchor:before,
+.markdown-body h4:hover .anchor:before,
+.markdown-body h5:hover .anchor:before,
+.markdown-body h6:hover .anchor:before {
+  visibility: visible;
+}
 .markdown-body p,
 .markdown-body blockquote,
 .markdown-body ul,
@@ -136,7 +173,7 @@ pub const MARKDOWN_BODY_CSS: &str = r#"
   word-wrap: normal;
   padding: 16px;
   overflow: auto;
-  font-size: 85%;
+  font-size: var(--crabix-mono-font-size, 85%);
   line-height: 1.45;
   background-color: #f7f7f7;
   -webkit-border-radius: 3px;
@@ -162,12 +199,12 @@ pub const MARKDOWN_BODY_CSS: &str = r#"
   content: normal;
 }
 .markdown-body code {
-  font-family: Consolas, "Liberation Mono", Menlo, Courier, monospace;
+  font-family: var(--crabix-mono-font, Consolas, "Liberation Mono", Menlo, Courier, monospace);
   padding: 0;
   padding-top: 0.2em;
   padding-bottom: 0.2em;
   margin: 0;
-  font-size: 85%;
+  font-size: var(--crabix-mono-font-size, 85%);
   background-color: rgba(0,0,0,0.04);
   -webkit-border-radius: 3px;
   border-radius: 3px;
@@ -221,4 +258,542 @@ pub const MARKDOWN_BODY_CSS: &str = r#"
   -moz-box-sizing: border-box;
   box-sizing: border-box;
   padding: 0;
-}"#;
+}
+.toc-toggle {
+  position: fixed;
+  top: 8px;
+  left: 8px;
+  z-index: 2;
+  padding: 2px 8px;
+  font-size: 16px;
+  color: #333;
+  background: #fff;
+  border: 1px solid #ddd;
+  border-radius: 3px;
+  cursor: pointer;
+}
+.toc-sidebar {
+  position: fixed;
+  top: 0;
+  bottom: 0;
+  left: 0;
+  z-index: 1;
+  width: 260px;
+  padding: 45px 12px 12px 12px;
+  overflow-y: auto;
+  font-family: "Helvetica Neue", Helvetica, "Segoe UI", Arial, freesans, sans-serif;
+  font-size: 14px;
+  background: #f7f7f7;
+  border-right: 1px solid #ddd;
+}
+.toc-sidebar ul {
+  margin: 0;
+  padding: 0;
+  list-style-type: none;
+}
+.toc-entry a {
+  display: block;
+  padding: 2px 6px;
+  color: #333;
+  text-decoration: none;
+  border-radius: 3px;
+  overflow: hidden;
+  white-space: nowrap;
+  text-overflow: ellipsis;
+}
+.toc-entry a:hover {
+  background-color: rgba(0,0,0,0.06);
+}
+.toc-entry.current a {
+  color: #4078c0;
+  font-weight: bold;
+  background-color: rgba(64,120,192,0.1);
+}
+.toc-level-2 { padding-left: 12px; }
+.toc-level-3 { padding-left: 24px; }
+.toc-level-4 { padding-left: 36px; }
+.toc-level-5 { padding-left: 48px; }
+.toc-level-6 { padding-left: 60px; }
+.with-toc .markdown-body {
+  margin-left: 260px;
+}
+.file-tree {
+  position: fixed;
+  top: 0;
+  bottom: 0;
+  left: 0;
+  z-index: 1;
+  width: 240px;
+  padding: 12px 0;
+  overflow-y: auto;
+  font-family: "Helvetica Neue", Helvetica, "Segoe UI", Arial, freesans, sans-serif;
+  font-size: 14px;
+  background: #f7f7f7;
+  border-right: 1px solid #ddd;
+}
+.file-tree ul {
+  margin: 0;
+  padding: 0;
+  list-style-type: none;
+}
+.file-tree-root {
+  padding: 0 12px 8px 12px;
+  font-weight: bold;
+  overflow: hidden;
+  text-overflow: ellipsis;
+}
+.file-tree-empty {
+  padding: 0 12px;
+  color: #777;
+}
+.file-tree-dir,
+.file-tree-file {
+  margin: 0 6px;
+  padding-top: 2px;
+  padding-bottom: 2px;
+  color: #333;
+  white-space: nowrap;
+  overflow: hidden;
+  text-overflow: ellipsis;
+  border-radius: 3px;
+  cursor: pointer;
+}
+.file-tree-dir:before {
+  content: "▾ ";
+}
+.file-tree-dir.collapsed:before {
+  content: "▸ ";
+}
+.file-tree-dir:hover,
+.file-tree-file:hover {
+  background-color: rgba(0,0,0,0.06);
+}
+.file-tree-file.current {
+  color: #4078c0;
+  font-weight: bold;
+  background-color: rgba(64,120,192,0.1);
+}
+.with-files .toc-toggle {
+  left: 248px;
+}
+.with-files .toc-sidebar {
+  left: 240px;
+}
+.with-files .markdown-body,
+.with-files .backlinks {
+  margin-left: 240px;
+}
+.with-files.with-toc .markdown-body,
+.with-files.with-toc .backlinks {
+  margin-left: 500px;
+}
+.search-toggle {
+  position: fixed;
+  top: 8px;
+  right: 8px;
+  z-index: 3;
+  padding: 2px 8px;
+  font-size: 14px;
+  background: #fff;
+  border: 1px solid #ddd;
+  border-radius: 3px;
+  cursor: pointer;
+}
+.search-panel {
+  position: fixed;
+  top: 0;
+  right: 0;
+  bottom: 0;
+  z-index: 2;
+  width: 360px;
+  padding: 45px 12px 12px 12px;
+  overflow-y: auto;
+  font-family: "Helvetica Neue", Helvetica, "Segoe UI", Arial, freesans, sans-serif;
+  font-size: 14px;
+  background: #f7f7f7;
+  border-left: 1px solid #ddd;
+  box-shadow: -2px 0 6px rgba(0,0,0,0.08);
+}
+.search-input {
+  width: 100%;
+  box-sizing: border-box;
+  padding: 4px 8px;
+  font-size: 14px;
+  border: 1px solid #ccc;
+  border-radius: 3px;
+}
+.search-summary {
+  margin: 8px 0;
+  color: #777;
+}
+.search-panel ul {
+  margin: 0;
+  padding: 0;
+  list-style-type: none;
+}
+.search-hit {
+  padding: 4px 6px;
+  border-radius: 3px;
+  cursor: pointer;
+}
+.search-hit:hover {
+  background-color: rgba(0,0,0,0.06);
+}
+.search-hit-location {
+  color: #4078c0;
+  font-size: 12px;
+}
+.search-hit-snippet {
+  color: #333;
+  word-break: break-word;
+}
+.search-hit-snippet mark {
+  background-color: #fff8c5;
+}
+.find-bar {
+  position: fixed;
+  top: 8px;
+  right: 48px;
+  z-index: 4;
+  display: flex;
+  align-items: center;
+  gap: 6px;
+  padding: 4px 8px;
+  font-family: "Helvetica Neue", Helvetica, "Segoe UI", Arial, freesans, sans-serif;
+  font-size: 13px;
+  color: #333;
+  background: #fff;
+  border: 1px solid #ddd;
+  border-radius: 3px;
+  box-shadow: 0 2px 6px rgba(0,0,0,0.12);
+}
+.find-bar[hidden] {
+  display: none;
+}
+.find-input {
+  width: 200px;
+  padding: 2px 6px;
+  font-size: 13px;
+  border: 1px solid #ccc;
+  border-radius: 3px;
+}
+.find-count {
+  min-width: 64px;
+  color: #777;
+}
+.find-count.error {
+  color: #c00;
+}
+.find-bar label {
+  font-family: Consolas, "Liberation Mono", Courier, monospace;
+  cursor: pointer;
+}
+.find-bar button {
+  padding: 0 6px;
+  color: inherit;
+  background: transparent;
+  border: 1px solid transparent;
+  border-radius: 3px;
+  cursor: pointer;
+}
+.find-bar button:hover {
+  border-color: #ddd;
+}
+mark.find-match {
+  color: inherit;
+  background-color: #fff8c5;
+}
+mark.find-match.current {
+  background-color: #ff9632;
+}
+.command-palette-backdrop {
+  position: fixed;
+  top: 0;
+  right: 0;
+  bottom: 0;
+  left: 0;
+  z-index: 5;
+  background: rgba(0,0,0,0.15);
+}
+.command-palette {
+  position: fixed;
+  top: 60px;
+  left: 50%;
+  z-index: 6;
+  width: 520px;
+  max-width: calc(100% - 40px);
+  transform: translateX(-50%);
+  padding: 8px;
+  font-family: "Helvetica Neue", Helvetica, "Segoe UI", Arial, freesans, sans-serif;
+  font-size: 14px;
+  color: #333;
+  background: #fff;
+  border: 1px solid #ddd;
+  border-radius: 3px;
+  box-shadow: 0 4px 12px rgba(0,0,0,0.2);
+}
+.command-palette-input {
+  width: 100%;
+  box-sizing: border-box;
+  padding: 6px 8px;
+  font-size: 14px;
+  border: 1px solid #ccc;
+  border-radius: 3px;
+}
+.command-palette ul {
+  max-height: 360px;
+  margin: 6px 0 0 0;
+  padding: 0;
+  overflow-y: auto;
+  list-style-type: none;
+}
+.command-palette-item {
+  display: flex;
+  justify-content: space-between;
+  gap: 12px;
+  padding: 4px 8px;
+  border-radius: 3px;
+  cursor: pointer;
+}
+.command-palette-item:hover {
+  background-color: rgba(0,0,0,0.06);
+}
+.command-palette-item.selected {
+  background-color: rgba(64,120,192,0.15);
+}
+.command-palette-keys {
+  color: #777;
+  font-family: Consolas, "Liberation Mono", Courier, monospace;
+  font-size: 12px;
+  white-space: nowrap;
+}
+.live-updates-paused {
+  position: fixed;
+  bottom: 8px;
+  right: 8px;
+  z-index: 4;
+  padding: 2px 8px;
+  font-family: "Helvetica Neue", Helvetica, "Segoe UI", Arial, freesans, sans-serif;
+  font-size: 12px;
+  color: #333;
+  background: #fff8c5;
+  border: 1px solid #d4a72c;
+  border-radius: 3px;
+}
+.status-message {
+  position: fixed;
+  bottom: 8px;
+  left: 8px;
+  z-index: 4;
+  max-width: 60%;
+  padding: 2px 8px;
+  font-family: "Helvetica Neue", Helvetica, "Segoe UI", Arial, freesans, sans-serif;
+  font-size: 12px;
+  color: #333;
+  background: #f6f8fa;
+  border: 1px solid #ddd;
+  border-radius: 3px;
+  overflow-wrap: anywhere;
+  cursor: default;
+}
+.focus-bar .crabix-focus {
+  box-shadow: -9px 0 0 #fff, -12px 0 0 #4078c0;
+}
+.focus-tint .crabix-focus {
+  background-color: rgba(255,212,59,0.2);
+}
+.crabix-focus-word {
+  position: absolute;
+  pointer-events: none;
+  border-bottom: 2px solid #4078c0;
+}
+.markdown-body details.front-matter {
+  margin-bottom: 16px;
+  padding: 6px 13px;
+  font-size: 85%;
+  color: #777;
+  border: 1px solid #ddd;
+  border-radius: 3px;
+}
+.markdown-body details.front-matter summary {
+  cursor: pointer;
+}
+.markdown-body details.front-matter table {
+  margin: 8px 0 0 0;
+}
+.markdown-body details.front-matter th {
+  text-align: left;
+}
+.markdown-body .front-matter-error {
+  color: #c00;
+}
+.markdown-body .wiki-link-new {
+  color: #c00;
+  cursor: help;
+  border-bottom: 1px dashed #c00;
+}
+.backlinks {
+  padding: 0 45px 24px 45px;
+  font-family: Helvetica, arial, freesans, clean, sans-serif;
+  font-size: 14px;
+  color: #777;
+}
+.with-toc .backlinks {
+  margin-left: 260px;
+}
+.backlinks h2 {
+  font-size: 14px;
+  text-transform: uppercase;
+  border-top: 1px solid #ddd;
+  padding-top: 12px;
+}
+.backlinks a {
+  color: #4183c4;
+  text-decoration: none;
+}
+body:has(.theme-dark) {
+  background: #0d1117;
+}
+.theme-dark .markdown-body {
+  color: #c9d1d9;
+  background: #0d1117;
+  border-color: #30363d;
+}
+.theme-dark .markdown-body h1,
+.theme-dark .markdown-body h2 {
+  border-bottom-color: #21262d;
+}
+.theme-dark .markdown-body h6,
+.theme-dark .markdown-body blockquote,
+.theme-dark .markdown-body details.front-matter {
+  color: #8b949e;
+}
+.theme-dark .markdown-body blockquote,
+.theme-dark .markdown-body details.front-matter {
+  border-color: #30363d;
+}
+.theme-dark .markdown-body a,
+.theme-dark .markdown-body .anchor {
+  color: #58a6ff;
+}
+.theme-dark .markdown-body code {
+  background-color: rgba(110,118,129,0.4);
+}
+.theme-dark .markdown-body pre code {
+  background-color: transparent;
+}
+.theme-dark .markdown-body table tr {
+  background-color: #0d1117;
+  border-top-color: #21262d;
+}
+.theme-dark .markdown-body table tr:nth-child(2n) {
+  background-color: #161b22;
+}
+.theme-dark .markdown-body table th,
+.theme-dark .markdown-body table td {
+  border-color: #30363d;
+}
+.theme-dark .backlinks {
+  color: #8b949e;
+}
+.theme-dark .backlinks h2 {
+  border-top-color: #30363d;
+}
+.theme-dark .backlinks a {
+  color: #58a6ff;
+}
+.theme-dark .file-tree {
+  background: #161b22;
+  border-right-color: #30363d;
+}
+.theme-dark .file-tree-dir,
+.theme-dark .file-tree-file {
+  color: #c9d1d9;
+}
+.theme-dark .file-tree-file.current {
+  color: #58a6ff;
+  background-color: rgba(88,166,255,0.15);
+}
+.theme-dark .search-toggle,
+.theme-dark .search-input {
+  color: #c9d1d9;
+  background: #0d1117;
+  border-color: #30363d;
+}
+.theme-dark .search-panel {
+  background: #161b22;
+  border-left-color: #30363d;
+}
+.theme-dark .search-hit-snippet {
+  color: #c9d1d9;
+}
+.theme-dark .search-hit-location {
+  color: #58a6ff;
+}
+.theme-dark .search-hit-snippet mark {
+  color: #c9d1d9;
+  background-color: rgba(187,128,9,0.4);
+}
+.theme-dark .find-bar,
+.theme-dark .find-input {
+  color: #c9d1d9;
+  background: #161b22;
+  border-color: #30363d;
+}
+.theme-dark mark.find-match {
+  background-color: rgba(187,128,9,0.4);
+}
+.theme-dark mark.find-match.current {
+  background-color: #9e6a03;
+}
+.theme-dark .command-palette,
+.theme-dark .command-palette-input {
+  color: #c9d1d9;
+  background: #161b22;
+  border-color: #30363d;
+}
+.theme-dark .command-palette-item:hover {
+  background-color: rgba(255,255,255,0.06);
+}
+.theme-dark .command-palette-item.selected {
+  background-color: rgba(88,166,255,0.15);
+}
+.theme-dark .command-palette-keys {
+  color: #8b949e;
+}
+.theme-dark .live-updates-paused {
+  color: #c9d1d9;
+  background: rgba(187,128,9,0.4);
+  border-color: #9e6a03;
+}
+.theme-dark .status-message {
+  color: #c9d1d9;
+  background: #161b22;
+  border-color: #30363d;
+}
+.theme-dark.focus-bar .crabix-focus {
+  box-shadow: -9px 0 0 #0d1117, -12px 0 0 #58a6ff;
+}
+.theme-dark.focus-tint .crabix-focus {
+  background-color: rgba(56,139,253,0.15);
+}
+body:has(.theme-dark) > .crabix-focus-word {
+  border-bottom-color: #58a6ff;
+}
+.theme-dark .toc-toggle {
+  color: #c9d1d9;
+  background: #161b22;
+  border-color: #30363d;
+}
+.theme-dark .toc-sidebar {
+  background: #161b22;
+  border-right-color: #30363d;
+}
+.theme-dark .toc-entry a {
+  color: #c9d1d9;
+}
+.theme-dark .toc-entry.current a {
+  color: #58a6ff;
+  background-color: rgba(88,166,255,0.15);
+}
+"##;
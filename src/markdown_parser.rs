@@ -1,86 +1,120 @@
 use markdown_it::common::sourcemap::SourceWithLineStarts;
 use markdown_it::parser::block::builtin::BlockParserRule;
 use markdown_it::parser::core::{CoreRule, Root};
-use markdown_it::parser::extset::RootExt;
+use markdown_it::parser::extset::{MarkdownItExt, NodeExt, RootExt};
 use markdown_it::parser::inline::builtin::InlineParserRule;
+use markdown_it::parser::inline::InlineRoot;
+use markdown_it::parser::inline::Text;
 use markdown_it::plugins::extra::syntect::{SyntectRule, SyntectSnippet};
 use markdown_it::plugins::html::html_block::HtmlBlock;
-use markdown_it::{MarkdownIt, Node};
-use std::cmp::min;
-
-#[derive(Debug, Copy, Clone, PartialEq)]
+use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+pub mod front_matter;
+pub mod heading_anchor;
+mod html_elements;
+pub mod task_list;
+pub mod toc_placeholder;
+pub mod wiki_link;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
 pub struct Spos {
     pub start_line: u32,
     pub end_line: u32,
 }
 
 impl Spos {
-    // Returns the closes to source_line Spos element that is used as attribute in HTML result
-    pub fn find(source_line: u32, sposes: &Vec<Spos>) -> Option<Spos> {
-        if sposes.is_empty() {
-            return None;
-        }
+    fn span(&self) -> u32 {
+        self.end_line - self.start_line
+    }
+}
 
-        // In case when souce_line is not in any spos range
-        let mut closest_element: Option<Spos> = None;
-        let mut closest_el_delta = u32::MAX;
-
-        let mut spos_res: Option<Spos> = None;
-        let mut spos_delta = u32::MAX;
-
-        for s in sposes {
-            if s.start_line <= source_line && s.end_line >= source_line {
-                let delta = s.end_line - s.start_line;
-                if delta < spos_delta {
-                    spos_res = Some(Spos {
-                        start_line: s.start_line,
-                        end_line: s.end_line,
-                    });
-                    spos_delta = delta;
-                }
+/// Source positions of a document, sorted once for lookups by line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SposIndex {
+    /// The innermost position of the lines from each line up to the next entry
+    segments: Vec<(u32, Option<Spos>)>,
+    /// The narrowest position ending at each line, sorted by the line
+    by_end: Vec<Spos>,
+    /// The narrowest position starting at each line, sorted by the line
+    by_start: Vec<Spos>,
+}
+
+impl SposIndex {
+    pub fn new(mut sposes: Vec<Spos>) -> SposIndex {
+        // Narrower positions first, earlier ones among equally wide
+        let rank = |s: &Spos| (s.span(), s.start_line, s.end_line);
+        sposes.sort_by_key(rank);
+        sposes.dedup();
+
+        let mut by_start = sposes.clone();
+        by_start.sort_by_key(|s| s.start_line);
+        by_start.dedup_by_key(|s| s.start_line);
+        let mut by_end = sposes.clone();
+        by_end.sort_by_key(|s| s.end_line);
+        by_end.dedup_by_key(|s| s.end_line);
+
+        let mut events: Vec<(u32, bool, Spos)> = sposes
+            .iter()
+            .flat_map(|s| [(s.start_line, true, *s), (s.end_line + 1, false, *s)])
+            .collect();
+        events.sort_by_key(|(line, _, _)| *line);
+        let mut segments: Vec<(u32, Option<Spos>)> = vec![];
+        let mut active = BTreeSet::new();
+        for (i, (line, starts, spos)) in events.iter().enumerate() {
+            if *starts {
+                active.insert(rank(spos));
+            } else {
+                active.remove(&rank(spos));
             }
-            if s.start_line.abs_diff(source_line) < closest_el_delta
-                || s.end_line.abs_diff(source_line) < closest_el_delta
-            {
-                closest_element = Some(Spos {
-                    start_line: s.start_line,
-                    end_line: s.end_line,
-                });
-                closest_el_delta = min(
-                    s.start_line.abs_diff(source_line),
-                    s.end_line.abs_diff(source_line),
-                );
-            } else if (s.start_line.abs_diff(source_line) == closest_el_delta
-                || s.end_line.abs_diff(source_line) == closest_el_delta)
-                && closest_element.is_some()
-            {
-                // There are two competitors that have the same delta,
-                // So we choose an element, that have lower delta between its end_line and start_line
-                let closest_el = closest_element.unwrap();
-                let closest_el_diff = closest_el.end_line - closest_el.start_line;
-                let candidate_diff = s.end_line - s.start_line;
-                if candidate_diff < closest_el_diff {
-                    closest_element = Some(Spos {
-                        start_line: s.start_line,
-                        end_line: s.end_line,
-                    });
-                }
+            if events.get(i + 1).is_some_and(|next| next.0 == *line) {
+                continue;
             }
+            let innermost = active.first().map(|(_, start_line, end_line)| Spos {
+                start_line: *start_line,
+                end_line: *end_line,
+            });
+            if segments.last().map(|(_, s)| *s) != Some(innermost) {
+                segments.push((*line, innermost));
+            }
+        }
+        SposIndex {
+            segments,
+            by_end,
+            by_start,
         }
+    }
 
-        if spos_res.is_some() {
-            return spos_res;
+    /// The narrowest position containing the line
+    pub fn innermost(&self, line: u32) -> Option<Spos> {
+        let segment = self.segments.partition_point(|(start, _)| *start <= line);
+        segment.checked_sub(1).and_then(|i| self.segments[i].1)
+    }
+
+    /// The innermost position containing the line, otherwise the closest one.
+    /// Narrower positions win among equally close ones, then earlier ones.
+    pub fn find(&self, line: u32) -> Option<Spos> {
+        if let Some(spos) = self.innermost(line) {
+            return Some(spos);
         }
-        if closest_element.is_some() {
-            return closest_element;
+        let before = self.by_end.partition_point(|s| s.end_line < line);
+        let before = before.checked_sub(1).map(|i| self.by_end[i]);
+        let after = self.by_start.partition_point(|s| s.start_line <= line);
+        let after = self.by_start.get(after).copied();
+        match (before, after) {
+            (Some(b), Some(a)) => {
+                let closer = (line - b.end_line, b.span()) <= (a.start_line - line, a.span());
+                Some(if closer { b } else { a })
+            }
+            (before, after) => before.or(after),
         }
-        return None;
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct SposesExt {
-    pub sposes: Vec<Spos>,
+    pub sposes: SposIndex,
 }
 
 impl RootExt for SposesExt {}
@@ -90,10 +124,16 @@ pub struct MarkdownParser {
 }
 
 fn add(md: &mut MarkdownIt) {
+    md.add_rule::<BlockMarkRule>()
+        .after::<BlockParserRule>()
+        .before::<InlineParserRule>();
     md.add_rule::<SyntaxPosRule>()
         .after::<BlockParserRule>()
         .after::<InlineParserRule>()
-        .after::<SyntectRule>();
+        .after::<SyntectRule>()
+        .after::<task_list::TaskListRule>()
+        .after::<heading_anchor::HeadingAnchorRule>()
+        .after::<toc_placeholder::TocPlaceholderRule>();
 }
 
 impl MarkdownParser {
@@ -102,127 +142,203 @@ impl MarkdownParser {
         markdown_it::plugins::cmark::add(&mut parser);
         markdown_it::plugins::html::add(&mut parser);
         markdown_it::plugins::extra::add(&mut parser);
+        front_matter::add(&mut parser);
+        task_list::add(&mut parser);
+        heading_anchor::add(&mut parser);
+        toc_placeholder::add(&mut parser);
+        wiki_link::add(&mut parser);
         add(&mut parser);
         MarkdownParser {
             parserEngine: parser,
         }
     }
 
+    pub fn set_code_theme(&mut self, theme: &'static str) {
+        markdown_it::plugins::extra::syntect::set_theme(&mut self.parserEngine, theme);
+    }
+
+    pub fn set_toc_depth(&mut self, depth: toc_placeholder::TocDepth) {
+        self.parserEngine.ext.insert(depth);
+    }
+
+    pub fn set_wiki_pages(&mut self, pages: wiki_link::WikiPages) {
+        self.parserEngine.ext.insert(pages);
+    }
+
+    /// Column positions of inline elements and text, only the live preview needs them
+    pub fn set_inline_positions(&mut self, enabled: bool) {
+        if enabled {
+            self.parserEngine.ext.insert(InlinePositions);
+        } else {
+            self.parserEngine.ext.remove::<InlinePositions>();
+        }
+    }
+
     pub fn parse(&mut self, src: &str) -> Node {
         self.parserEngine.parse(src)
     }
 }
 
+// Nodes that exist before inline parsing are blocks, the others are inline elements and text.
+// Tight lists hold the inline content of their items without a paragraph.
+#[derive(Debug)]
+struct BlockNode;
+impl NodeExt for BlockNode {}
+
+#[doc(hidden)]
+pub struct BlockMarkRule;
+impl CoreRule for BlockMarkRule {
+    fn run(root: &mut Node, _: &MarkdownIt) {
+        root.walk_mut(|node, _| {
+            if !node.is::<InlineRoot>() {
+                node.ext.insert(BlockNode);
+            }
+        });
+    }
+}
+
+#[derive(Debug)]
+struct InlinePositions;
+impl MarkdownItExt for InlinePositions {}
+
 #[doc(hidden)]
 pub struct SyntaxPosRule;
 impl CoreRule for SyntaxPosRule {
-    fn run(root: &mut Node, _: &MarkdownIt) {
+    fn run(root: &mut Node, md: &MarkdownIt) {
         let source = root.cast::<Root>().unwrap().content.as_str();
         let mapping = SourceWithLineStarts::new(source);
+        let columns = md.ext.contains::<InlinePositions>();
 
         let mut sposes: Vec<Spos> = vec![];
-        root.walk_mut(|node, _| {
-            if let Some(map) = node.srcmap {
-                if node.node_type.name == "markdown_it::plugins::extra::syntect::SyntectSnippet" {
-                    // As improvement we can fork/rewrite SyntectSnippet plugin ad paste data-spos
-                    // there to avoid redundant copy/replace operations
-                    if let Some(ss) = node.node_value.as_any().downcast_ref::<SyntectSnippet>() {
-                        let ((start_line, _startcol), (end_line, _endcol)) =
-                            map.get_positions(&mapping);
-
-                        if !ss.html.starts_with("<pre ") {
-                            panic!("Unexpected Syntect Snippet result: {:?}", ss);
-                        }
-                        let selector: String =
-                            format!("data-spos=\"{}-{}\" ", start_line, end_line);
-
-                        let mut from_result = String::from(&ss.html[0..=4]);
-                        from_result.push_str(&selector);
-                        from_result.push_str(&ss.html[5..]);
-                        node.replace(SyntectSnippet { html: from_result });
-
-                        sposes.push(Spos {
-                            start_line,
-                            end_line,
-                        });
-                    } else {
-                        panic!("downcast_ref for SyntectSnippet is failed");
-                    }
-                } else if node.node_type.name == "markdown_it::plugins::html::html_block::HtmlBlock"
-                {
-                    // HTML in markdown is pain in the ass
-                    // If html inline rendering has artifacts, try to remove this code
-                    if let Some(ss) = node.node_value.as_any().downcast_ref::<HtmlBlock>() {
-                        let start_html_tag_index =
-                            ss.content.chars().take_while(|c| *c != '<').count();
-                        if ss.content.len() <= start_html_tag_index + 1 {
-                            return;
-                        }
-
-                        if !ss
-                            .content
-                            .chars()
-                            .nth(start_html_tag_index + 1)
-                            .unwrap()
-                            .is_alphabetic()
-                        {
-                            return;
-                        }
-
-                        let last_index_of_html_tag = ss.content[start_html_tag_index + 1..]
-                            .chars()
-                            .take_while(|c| c.is_alphabetic())
-                            .count()
-                            + start_html_tag_index;
-
-                        let ((start_line, _startcol), (end_line, _endcol)) =
-                            map.get_positions(&mapping);
-                        let selector: String =
-                            format!(" data-spos=\"{}-{}\"", start_line, end_line);
-                        let mut html_result =
-                            String::with_capacity(ss.content.len() + selector.len() + 42);
-                        html_result.push_str(&ss.content[..=last_index_of_html_tag]);
-                        html_result.push_str(&selector);
-                        html_result.push_str(&ss.content[last_index_of_html_tag + 1..]);
-                        node.replace(HtmlBlock {
-                            content: html_result,
-                        });
-
-                        sposes.push(Spos {
-                            start_line,
-                            end_line,
-                        });
-                    } else {
-                        panic!("downcast_ref for HtmlBlock is failed");
-                    }
-                } else {
-                    if node.node_type.name == "markdown_it::parser::core::root::Root"
-                        || node.node_type.name
-                            == "markdown_it::parser::inline::builtin::skip_text::Text"
-                    {
-                        // root position quite clear. First line and last line :)
-                        // Text is always inside higher html tag
-                    } else {
-                        let ((start_line, _startcol), (end_line, _endcol)) =
-                            map.get_positions(&mapping);
-                        let selector: String = format!("{}-{}", start_line, end_line);
-                        node.attrs.push(("data-spos", selector));
-                        sposes.push(Spos {
-                            start_line,
-                            end_line,
-                        });
-                    }
+        for node in root.children.iter_mut() {
+            add_positions(node, &mapping, columns, &mut sposes);
+        }
+        let mutrut = root.cast_mut::<Root>().unwrap();
+        mutrut.ext.insert(SposesExt {
+            sposes: SposIndex::new(sposes),
+        });
+    }
+}
+
+// Block elements get `data-spos="START-END"` with their lines. With `columns` inline elements
+// and text get `data-spos="LINE:COLUMN-LINE:COLUMN"` with the positions of their first and last
+// characters, otherwise inline elements get their lines and text stays as it is.
+// Only block positions are collected, the preview finds inline elements inside the blocks.
+fn add_positions(
+    node: &mut Node,
+    mapping: &SourceWithLineStarts,
+    columns: bool,
+    sposes: &mut Vec<Spos>,
+) {
+    let inline = !node.ext.contains::<BlockNode>();
+    if let Some(map) = node.srcmap {
+        if inline && !columns {
+            if !node.is::<Text>() {
+                let ((start_line, _startcol), (end_line, _endcol)) = map.get_positions(mapping);
+                node.attrs
+                    .push(("data-spos", format!("{}-{}", start_line, end_line)));
+            }
+        } else if inline {
+            let ((start_line, start_column), (end_line, end_column)) = map.get_positions(mapping);
+            let selector = format!(
+                "{}:{}-{}:{}",
+                start_line, start_column, end_line, end_column
+            );
+            if node.is::<Text>() {
+                // Text can't have attributes, so it is wrapped in a span
+                let text = std::mem::replace(node, Node::new(SourceSpan));
+                node.srcmap = text.srcmap;
+                node.children.push(text);
+                node.attrs.push(("data-spos", selector));
+                return;
+            }
+            node.attrs.push(("data-spos", selector));
+        } else if node.node_type.name == "markdown_it::plugins::extra::syntect::SyntectSnippet" {
+            // As improvement we can fork/rewrite SyntectSnippet plugin ad paste data-spos
+            // there to avoid redundant copy/replace operations
+            if let Some(ss) = node.node_value.as_any().downcast_ref::<SyntectSnippet>() {
+                let ((start_line, _startcol), (end_line, _endcol)) = map.get_positions(mapping);
+
+                if !ss.html.starts_with("<pre ") {
+                    panic!("Unexpected Syntect Snippet result: {:?}", ss);
                 }
+                let selector: String = format!("data-spos=\"{}-{}\" ", start_line, end_line);
+
+                let mut from_result = String::from(&ss.html[0..=4]);
+                from_result.push_str(&selector);
+                from_result.push_str(&ss.html[5..]);
+                node.replace(SyntectSnippet { html: from_result });
+
+                sposes.push(Spos {
+                    start_line,
+                    end_line,
+                });
+            } else {
+                panic!("downcast_ref for SyntectSnippet is failed");
             }
-        });
-        let mutrut = root.cast_mut::<Root>().unwrap();
-        mutrut.ext.insert(SposesExt { sposes });
+        } else if node.node_type.name == "markdown_it::plugins::html::html_block::HtmlBlock" {
+            // Every top-level element of the block gets its own lines
+            if let Some(ss) = node.node_value.as_any().downcast_ref::<HtmlBlock>() {
+                let elements = html_elements::top_level_elements(&ss.content);
+                if elements.is_empty() {
+                    return;
+                }
+                let ((start_line, _startcol), (_end_line, _endcol)) = map.get_positions(mapping);
+                let line_at =
+                    |offset: usize| start_line + ss.content[..offset].matches('\n').count() as u32;
+                let mut html_result = String::with_capacity(ss.content.len() + 24 * elements.len());
+                let mut copied = 0;
+                for element in elements {
+                    let spos = Spos {
+                        start_line: line_at(element.start),
+                        // Trailing line breaks of an unclosed element aren't its lines
+                        end_line: line_at(ss.content[..element.end].trim_end().len()),
+                    };
+                    html_result.push_str(&ss.content[copied..element.name_end]);
+                    html_result.push_str(&format!(
+                        " data-spos=\"{}-{}\"",
+                        spos.start_line, spos.end_line
+                    ));
+                    copied = element.name_end;
+                    sposes.push(spos);
+                }
+                html_result.push_str(&ss.content[copied..]);
+                node.replace(HtmlBlock {
+                    content: html_result,
+                });
+            } else {
+                panic!("downcast_ref for HtmlBlock is failed");
+            }
+        } else {
+            let ((start_line, _startcol), (end_line, _endcol)) = map.get_positions(mapping);
+            let selector: String = format!("{}-{}", start_line, end_line);
+            node.attrs.push(("data-spos", selector));
+            sposes.push(Spos {
+                start_line,
+                end_line,
+            });
+        }
+    }
+    for child in node.children.iter_mut() {
+        add_positions(child, mapping, columns, sposes);
+    }
+}
+
+/// Text with its source position, see [SyntaxPosRule]
+#[derive(Debug)]
+pub struct SourceSpan;
+impl NodeValue for SourceSpan {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.open("span", &node.attrs);
+        fmt.contents(&node.children);
+        fmt.close("span");
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::markdown_parser::{MarkdownParser, Spos};
+    use crate::markdown_parser::{MarkdownParser, Spos, SposIndex};
+    use proptest::prelude::*;
 
     fn spos(start_line: u32, end_line: u32) -> Spos {
         Spos {
@@ -233,19 +349,96 @@ mod tests {
 
     #[test]
     fn spos_test() {
-        let sposes = vec![spos(3, 4), spos(4, 4), spos(6, 7)];
-        assert_eq!(Spos::find(5, &sposes).unwrap(), spos(4, 4));
-        assert_eq!(Spos::find(6, &sposes).unwrap(), spos(6, 7));
+        let sposes = SposIndex::new(vec![spos(3, 4), spos(4, 4), spos(6, 7)]);
+        assert_eq!(sposes.find(5).unwrap(), spos(4, 4));
+        assert_eq!(sposes.find(6).unwrap(), spos(6, 7));
+
+        let sposes = SposIndex::new(vec![spos(4, 4), spos(3, 4), spos(6, 6)]);
+        assert_eq!(sposes.find(5).unwrap(), spos(4, 4));
+        assert_eq!(SposIndex::new(vec![]).find(5), None);
+    }
+
+    // Scans every position, the index has to give the same results
+    fn linear_find(line: u32, sposes: &[Spos]) -> Option<Spos> {
+        let containing = sposes
+            .iter()
+            .filter(|s| s.start_line <= line && line <= s.end_line)
+            .min_by_key(|s| s.end_line - s.start_line);
+        let distance = |s: &Spos| s.start_line.abs_diff(line).min(s.end_line.abs_diff(line));
+        let closest = sposes
+            .iter()
+            .min_by_key(|s| (distance(s), s.end_line - s.start_line));
+        containing.or(closest).copied()
+    }
 
-        let sposes = vec![spos(4, 4), spos(3, 4), spos(6, 6)];
-        assert_eq!(Spos::find(5, &sposes).unwrap(), spos(4, 4));
+    fn sposes() -> impl Strategy<Value = Vec<Spos>> {
+        prop::collection::vec((1..60u32, 0..12u32), 0..40).prop_map(|sposes| {
+            sposes
+                .into_iter()
+                .map(|(start_line, lines)| spos(start_line, start_line + lines))
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn spos_index_matches_linear_scan(sposes in sposes(), line in 0..80u32) {
+            let index = SposIndex::new(sposes.clone());
+            // Ties go to the earlier position, like the document order of the parser
+            let mut sorted = sposes;
+            sorted.sort_by_key(|s| s.start_line);
+            prop_assert_eq!(index.find(line), linear_find(line, &sorted));
+        }
+
+        #[test]
+        fn innermost_spos_contains_line(sposes in sposes(), line in 0..80u32) {
+            let index = SposIndex::new(sposes.clone());
+            let contains = |s: &Spos| s.start_line <= line && line <= s.end_line;
+            match index.innermost(line) {
+                Some(innermost) => {
+                    prop_assert!(contains(&innermost));
+                    prop_assert!(sposes
+                        .iter()
+                        .filter(|s| contains(s))
+                        .all(|s| s.end_line - s.start_line >= innermost.end_line - innermost.start_line));
+                }
+                None => prop_assert!(!sposes.iter().any(contains)),
+            }
+        }
     }
 
     #[test]
     fn header_test() {
         let parser = &mut MarkdownParser::new();
         let html = parser.parse("# hello").render();
-        assert_eq!(html.trim(), r#"<h1 data-spos="1-1">hello</h1>"#);
+        assert_eq!(
+            html.trim(),
+            r##"<h1 id="hello" data-spos="1-1"><a class="anchor" href="#hello" aria-hidden="true"></a>hello</h1>"##
+        );
+    }
+
+    #[test]
+    fn inline_positions_test() {
+        let parser = &mut MarkdownParser::new();
+        let source = "Some *long*\nparagraph `code`";
+        // Rendered documents only have line positions
+        assert_eq!(
+            parser.parse(source).render().trim(),
+            r##"<p data-spos="1-2">Some <em data-spos="1-1">long</em>
+paragraph <code data-spos="2-2">code</code></p>"##
+        );
+
+        parser.set_inline_positions(true);
+        let ast = parser.parse(source);
+        assert_eq!(
+            ast.render().trim(),
+            r##"<p data-spos="1-2"><span data-spos="1:1-1:5">Some </span><em data-spos="1:6-1:11"><span data-spos="1:7-1:10">long</span></em>
+<span data-spos="2:1-2:10">paragraph </span><code data-spos="2:11-2:16"><span data-spos="2:12-2:15">code</span></code></p>"##
+        );
+        // Inline elements aren't blocks to scroll to
+        let root = ast.cast::<markdown_it::parser::core::Root>().unwrap();
+        let sposes = &root.ext.get::<super::SposesExt>().unwrap().sposes;
+        assert_eq!(sposes.find(2), Some(spos(1, 2)));
     }
 
     #[test]
@@ -277,7 +470,7 @@ Metus sapien molestie cursus sollicitudin vivamus dignissim condimentum pretium
             .render();
         assert!(html.starts_with(
             r#"<!--This is a comment. Comments are not displayed in the browser-->   
-<p data-spos="3-6" align = "left">
+<p data-spos="3-5" align = "left">
 Metus sapien molestie cursus sollicitudin vivamus dignissim condimentum pretium velit.
 </p>
 <!--This is a comment. Comments are not displayed in the browser-->   
@@ -330,15 +523,47 @@ Metus sapien molestie cursus sollicitudin vivamus dignissim condimentum pretium
             .render();
         assert_eq!(
             html,
-            r#"<p data-spos="2-3" align = "left">
+            r##"<p data-spos="2-3" align = "left">
 Metus sapien molestie cursus sollicitudin vivamus dignissim condimentum pretium velit.
 </p>
-<p align = "left">
+<p data-spos="6-8" align = "left">
 Metus sapien molestie cursus sollicitudin vivamus dignissim condimentum pretium velit.
 </p>
-<h1 data-spos="10-10">Some text</h1>
+<h1 id="some-text" data-spos="10-10"><a class="anchor" href="#some-text" aria-hidden="true"></a>Some text</h1>
+"##
+        );
+    }
+
+    #[test]
+    fn html_block_elements() {
+        let parser = &mut MarkdownParser::new();
+        let source = r#"<p align="center">
+  <img src="logo.svg"/>
+</p>
+<!-- badges -->
+  <a href="ci"><img src="ci.svg"></a>
+<div>
+text
+</div>
+"#;
+        let ast = parser.parse(source);
+        assert_eq!(
+            ast.render(),
+            r#"<p data-spos="1-3" align="center">
+  <img src="logo.svg"/>
+</p>
+<!-- badges -->
+  <a data-spos="5-5" href="ci"><img src="ci.svg"></a>
+<div data-spos="6-8">
+text
+</div>
 "#
         );
+        let root = ast.cast::<markdown_it::parser::core::Root>().unwrap();
+        let sposes = &root.ext.get::<super::SposesExt>().unwrap().sposes;
+        assert_eq!(sposes.find(2), Some(spos(1, 3)));
+        assert_eq!(sposes.find(5), Some(spos(5, 5)));
+        assert_eq!(sposes.find(7), Some(spos(6, 8)));
     }
 
     #[test]
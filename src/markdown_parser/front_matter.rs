@@ -0,0 +1,262 @@
+//! YAML (`---`) and TOML (`+++`) front matter at the beginning of a document
+use markdown_it::parser::block::{BlockRule, BlockState};
+use markdown_it::parser::core::Root;
+use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrontMatterFormat {
+    Yaml,
+    Toml,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrontMatter {
+    /// Top level keys with their values formatted for display
+    pub fields: Vec<(String, String)>,
+    pub title: Option<String>,
+    pub theme: Option<String>,
+    pub toc: Option<bool>,
+    /// Heading levels listed by `[TOC]` markers, out of range values are ignored later
+    pub toc_min_depth: Option<i64>,
+    pub toc_max_depth: Option<i64>,
+    /// Set when the front matter can't be parsed, `raw` is displayed instead of the fields
+    pub error: Option<String>,
+    pub raw: String,
+    /// Number of source lines including both fences
+    pub lines: usize,
+}
+
+impl FrontMatter {
+    fn from_yaml(raw: &str) -> Result<Self, String> {
+        use serde_yaml::{Mapping, Value};
+
+        fn display(value: &Value) -> String {
+            match value {
+                Value::Null => String::new(),
+                Value::Bool(b) => b.to_string(),
+                Value::Number(n) => n.to_string(),
+                Value::String(s) => s.clone(),
+                Value::Sequence(items) => items.iter().map(display).collect::<Vec<_>>().join(", "),
+                Value::Mapping(_) | Value::Tagged(_) => {
+                    serde_json::to_string(value).unwrap_or_default()
+                }
+            }
+        }
+
+        let mapping: Mapping = match serde_yaml::from_str(raw).map_err(|e| e.to_string())? {
+            Value::Mapping(mapping) => mapping,
+            Value::Null => Mapping::new(),
+            _ => return Err("front matter is not a mapping".into()),
+        };
+        Ok(FrontMatter {
+            fields: mapping
+                .iter()
+                .map(|(k, v)| (display(k), display(v)))
+                .collect(),
+            title: mapping.get("title").and_then(Value::as_str).map(Into::into),
+            theme: mapping.get("theme").and_then(Value::as_str).map(Into::into),
+            toc: mapping.get("toc").and_then(Value::as_bool),
+            toc_min_depth: mapping.get("toc_min_depth").and_then(Value::as_i64),
+            toc_max_depth: mapping.get("toc_max_depth").and_then(Value::as_i64),
+            ..Default::default()
+        })
+    }
+
+    fn from_toml(raw: &str) -> Result<Self, String> {
+        use toml::{Table, Value};
+
+        fn display(value: &Value) -> String {
+            match value {
+                Value::String(s) => s.clone(),
+                Value::Datetime(d) => d.to_string(),
+                Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join(", "),
+                _ => value.to_string(),
+            }
+        }
+
+        let table: Table = raw
+            .parse()
+            .map_err(|e: toml::de::Error| e.message().to_string())?;
+        Ok(FrontMatter {
+            fields: table.iter().map(|(k, v)| (k.clone(), display(v))).collect(),
+            title: table.get("title").and_then(Value::as_str).map(Into::into),
+            theme: table.get("theme").and_then(Value::as_str).map(Into::into),
+            toc: table.get("toc").and_then(Value::as_bool),
+            toc_min_depth: table.get("toc_min_depth").and_then(Value::as_integer),
+            toc_max_depth: table.get("toc_max_depth").and_then(Value::as_integer),
+            ..Default::default()
+        })
+    }
+}
+
+/// Parses front matter if the source starts with it
+pub fn extract(src: &str) -> Option<FrontMatter> {
+    let mut lines = src.split_inclusive('\n');
+    let (format, closing): (_, &[&str]) = match lines.next()?.trim_end() {
+        "---" => (FrontMatterFormat::Yaml, &["---", "..."]),
+        "+++" => (FrontMatterFormat::Toml, &["+++"]),
+        _ => return None,
+    };
+
+    let mut raw = String::new();
+    let mut line_count = 1;
+    for line in lines {
+        line_count += 1;
+        if closing.contains(&line.trim_end()) {
+            let parsed = match format {
+                FrontMatterFormat::Yaml => FrontMatter::from_yaml(&raw),
+                FrontMatterFormat::Toml => FrontMatter::from_toml(&raw),
+            };
+            let mut front_matter = parsed.unwrap_or_else(|error| FrontMatter {
+                error: Some(error),
+                ..Default::default()
+            });
+            front_matter.raw = raw;
+            front_matter.lines = line_count;
+            return Some(front_matter);
+        }
+        raw.push_str(line);
+    }
+    // Without the closing fence it is not a front matter
+    None
+}
+
+#[derive(Debug)]
+pub struct FrontMatterBlock {
+    pub front_matter: FrontMatter,
+}
+
+impl NodeValue for FrontMatterBlock {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = vec![("class", "front-matter".to_string())];
+        attrs.extend(node.attrs.iter().cloned());
+        fmt.cr();
+        fmt.open("details", &attrs);
+        fmt.open("summary", &[]);
+        fmt.text("Metadata");
+        fmt.close("summary");
+        fmt.cr();
+        if let Some(error) = &self.front_matter.error {
+            fmt.open("p", &[("class", "front-matter-error".into())]);
+            fmt.text(error);
+            fmt.close("p");
+            fmt.cr();
+            fmt.open("pre", &[]);
+            fmt.text(&self.front_matter.raw);
+            fmt.close("pre");
+            fmt.cr();
+        } else {
+            fmt.open("table", &[]);
+            fmt.cr();
+            for (key, value) in self.front_matter.fields.iter() {
+                fmt.open("tr", &[]);
+                fmt.open("th", &[]);
+                fmt.text(key);
+                fmt.close("th");
+                fmt.open("td", &[]);
+                fmt.text(value);
+                fmt.close("td");
+                fmt.close("tr");
+                fmt.cr();
+            }
+            fmt.close("table");
+            fmt.cr();
+        }
+        fmt.close("details");
+        fmt.cr();
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    md.block.add_rule::<FrontMatterScanner>().before_all();
+}
+
+#[doc(hidden)]
+pub struct FrontMatterScanner;
+impl BlockRule for FrontMatterScanner {
+    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+        // Only the very first line of the document can open a front matter
+        if state.line != 0 || !state.node.is::<Root>() {
+            return None;
+        }
+        let front_matter = extract(state.src)?;
+        let lines = front_matter.lines;
+        Some((Node::new(FrontMatterBlock { front_matter }), lines))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract;
+    use crate::markdown_parser::MarkdownParser;
+
+    #[test]
+    fn yaml_front_matter() {
+        let src = "---\ntitle: Release notes\ntags: [rust, markdown]\ntoc: true\n---\n# Hello\n";
+        let fm = extract(src).unwrap();
+        assert_eq!(fm.title.as_deref(), Some("Release notes"));
+        assert_eq!(fm.toc, Some(true));
+        assert_eq!(fm.theme, None);
+        assert_eq!(fm.toc_min_depth, None);
+        assert_eq!(fm.lines, 5);
+
+        let fm = extract("---\ntoc_min_depth: 2\ntoc_max_depth: 3\n---\n").unwrap();
+        assert_eq!((fm.toc_min_depth, fm.toc_max_depth), (Some(2), Some(3)));
+
+        let parser = &mut MarkdownParser::new();
+        let html = parser.parse(src).render();
+        assert_eq!(
+            html,
+            r##"<details class="front-matter" data-spos="1-5"><summary>Metadata</summary>
+<table>
+<tr><th>title</th><td>Release notes</td></tr>
+<tr><th>tags</th><td>rust, markdown</td></tr>
+<tr><th>toc</th><td>true</td></tr>
+</table>
+</details>
+<h1 id="hello" data-spos="6-6"><a class="anchor" href="#hello" aria-hidden="true"></a>Hello</h1>
+"##
+        );
+    }
+
+    #[test]
+    fn toml_front_matter() {
+        let fm = extract("+++\ntheme = \"dark\"\ndate = 2023-01-05\n+++\n").unwrap();
+        assert_eq!(fm.theme.as_deref(), Some("dark"));
+        assert_eq!(
+            extract("+++\ntoc_max_depth = 2\n+++\n")
+                .unwrap()
+                .toc_max_depth,
+            Some(2)
+        );
+        assert_eq!(
+            fm.fields,
+            vec![
+                ("theme".to_string(), "dark".to_string()),
+                ("date".to_string(), "2023-01-05".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_front_matter() {
+        let fm = extract("---\ntitle: [oops\n---\ntext").unwrap();
+        assert!(fm.error.is_some());
+        assert_eq!(fm.raw, "title: [oops\n");
+
+        let parser = &mut MarkdownParser::new();
+        let html = parser.parse("---\ntitle: [oops\n---\ntext").render();
+        assert!(html.contains("<pre>title: [oops\n</pre>"));
+        assert!(html.ends_with("<p data-spos=\"4-4\">text</p>\n"));
+    }
+
+    #[test]
+    fn not_a_front_matter() {
+        assert_eq!(extract("---\nno closing fence"), None);
+        assert_eq!(extract("\n---\ntitle: a\n---\n"), None);
+
+        let parser = &mut MarkdownParser::new();
+        let html = parser.parse("text\n\n---\ntitle: a\n---\n").render();
+        assert!(!html.contains("front-matter"));
+    }
+}
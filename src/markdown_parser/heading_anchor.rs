@@ -0,0 +1,211 @@
+//! GitHub-compatible `id` attributes and permalinks for headings
+use super::Spos;
+use markdown_it::common::sourcemap::SourceWithLineStarts;
+use markdown_it::parser::core::{CoreRule, Root};
+use markdown_it::parser::extset::RootExt;
+use markdown_it::parser::inline::builtin::InlineParserRule;
+use markdown_it::parser::inline::{Text, TextSpecial};
+use markdown_it::plugins::cmark::block::heading::ATXHeading;
+use markdown_it::plugins::cmark::block::lheading::SetextHeader;
+use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+use std::collections::HashMap;
+
+#[derive(Debug)]
+pub struct HeadingAnchor {
+    pub slug: String,
+}
+
+impl NodeValue for HeadingAnchor {
+    fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
+        let attrs = [
+            ("class", "anchor".into()),
+            ("href", format!("#{}", self.slug)),
+            ("aria-hidden", "true".into()),
+        ];
+        fmt.open("a", &attrs);
+        fmt.close("a");
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    pub level: u8,
+    pub title: String,
+    pub slug: String,
+    pub spos: Spos,
+}
+
+impl Heading {
+    // Returns the heading of the section that contains source_line
+    pub fn section_at(source_line: u32, headings: &[Heading]) -> Option<&Heading> {
+        headings
+            .iter()
+            .take_while(|h| h.spos.start_line <= source_line)
+            .last()
+    }
+}
+
+/// All document headings in the source order
+#[derive(Debug, Clone)]
+pub struct HeadingsExt {
+    pub headings: Vec<Heading>,
+}
+
+impl RootExt for HeadingsExt {}
+
+pub fn add(md: &mut MarkdownIt) {
+    md.add_rule::<HeadingAnchorRule>()
+        .after::<InlineParserRule>();
+}
+
+/// Produces unique slugs the same way GitHub does,
+/// repeated headings get `-1`, `-2`, ... suffixes
+#[derive(Debug, Default)]
+pub struct Slugger {
+    occurrences: HashMap<String, u32>,
+}
+
+impl Slugger {
+    pub fn slug(&mut self, text: &str) -> String {
+        let base = slugify(text);
+        let mut slug = base.clone();
+        while self.occurrences.contains_key(&slug) {
+            let count = self.occurrences.get_mut(&base).unwrap();
+            *count += 1;
+            slug = format!("{}-{}", base, count);
+        }
+        self.occurrences.insert(slug.clone(), 0);
+        slug
+    }
+}
+
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Concatenated text of all inline children, e.g. `Install *now*` -> `Install now`
+pub fn text_content(node: &Node) -> String {
+    let mut text = String::new();
+    node.walk(|node, _| {
+        if let Some(t) = node.cast::<Text>() {
+            text.push_str(&t.content);
+        } else if let Some(t) = node.cast::<TextSpecial>() {
+            text.push_str(&t.content);
+        }
+    });
+    text
+}
+
+#[doc(hidden)]
+pub struct HeadingAnchorRule;
+impl CoreRule for HeadingAnchorRule {
+    fn run(root: &mut Node, _: &MarkdownIt) {
+        let source = root.cast::<Root>().unwrap().content.as_str();
+        let mapping = SourceWithLineStarts::new(source);
+
+        let mut slugger = Slugger::default();
+        let mut headings = vec![];
+        root.walk_mut(|node, _| {
+            let level = if let Some(h) = node.cast::<ATXHeading>() {
+                h.level
+            } else if let Some(h) = node.cast::<SetextHeader>() {
+                h.level
+            } else {
+                return;
+            };
+            let title = text_content(node);
+            let slug = slugger.slug(&title);
+            if let Some(map) = node.srcmap {
+                let ((start_line, _), (end_line, _)) = map.get_positions(&mapping);
+                headings.push(Heading {
+                    level,
+                    title,
+                    slug: slug.clone(),
+                    spos: Spos {
+                        start_line,
+                        end_line,
+                    },
+                });
+            }
+            node.attrs.push(("id", slug.clone()));
+            node.children.insert(0, Node::new(HeadingAnchor { slug }));
+        });
+        let root = root.cast_mut::<Root>().unwrap();
+        root.ext.insert(HeadingsExt { headings });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Heading, HeadingsExt, Slugger};
+    use crate::markdown_parser::{MarkdownParser, Spos};
+    use markdown_it::parser::core::Root;
+
+    #[test]
+    fn github_slugs() {
+        let mut slugger = Slugger::default();
+        assert_eq!(slugger.slug("Setup"), "setup");
+        assert_eq!(slugger.slug("What's new in v0.2.1?"), "whats-new-in-v021");
+        assert_eq!(
+            slugger.slug("snake_case & kebab-case"),
+            "snake_case--kebab-case"
+        );
+        assert_eq!(slugger.slug("Привіт, світ"), "привіт-світ");
+        assert_eq!(slugger.slug("Setup"), "setup-1");
+        assert_eq!(slugger.slug("Setup"), "setup-2");
+        assert_eq!(slugger.slug("Setup 1"), "setup-1-1");
+    }
+
+    #[test]
+    fn heading_ids() {
+        let parser = &mut MarkdownParser::new();
+        let html = parser
+            .parse("# Hello `world`\n\nText\n===\n\n## Hello world")
+            .render();
+        assert_eq!(
+            html,
+            r##"<h1 id="hello-world" data-spos="1-1"><a class="anchor" href="#hello-world" aria-hidden="true"></a>Hello <code data-spos="1-1">world</code></h1>
+<h1 id="text" data-spos="3-4"><a class="anchor" href="#text" aria-hidden="true"></a>Text</h1>
+<h2 id="hello-world-1" data-spos="6-6"><a class="anchor" href="#hello-world-1" aria-hidden="true"></a>Hello world</h2>
+"##
+        );
+    }
+
+    #[test]
+    fn collect_headings() {
+        let parser = &mut MarkdownParser::new();
+        let ast = parser.parse("intro\n# Title\n\ntext\n\nSub\n===\n### *Deep*\n");
+        let root = ast.cast::<Root>().unwrap();
+        let headings = &root.ext.get::<HeadingsExt>().unwrap().headings;
+        let heading = |level, title: &str, start_line, end_line| Heading {
+            level,
+            title: title.into(),
+            slug: title.to_lowercase(),
+            spos: Spos {
+                start_line,
+                end_line,
+            },
+        };
+        assert_eq!(
+            headings,
+            &vec![
+                heading(1, "Title", 2, 2),
+                heading(1, "Sub", 6, 7),
+                heading(3, "Deep", 8, 8)
+            ]
+        );
+        assert_eq!(Heading::section_at(1, headings), None);
+        assert_eq!(Heading::section_at(5, headings).unwrap().slug, "title");
+        assert_eq!(Heading::section_at(7, headings).unwrap().slug, "sub");
+        assert_eq!(Heading::section_at(100, headings).unwrap().slug, "deep");
+    }
+}
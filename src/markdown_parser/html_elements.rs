@@ -0,0 +1,185 @@
+//! Top-level elements of raw HTML blocks, found by a small tokenizer.
+//! Comments, doctypes and processing instructions aren't elements,
+//! closing tags without an opening one are skipped.
+
+/// Element of an HTML block, offsets are bytes of the block content
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HtmlElement {
+    pub start: usize,
+    /// Where attributes can be added, right after the tag name
+    pub name_end: usize,
+    /// After the closing tag, the end of the content when the element isn't closed
+    pub end: usize,
+}
+
+// Elements without content and closing tag
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+// Elements whose content is text up to their closing tag
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["script", "style", "textarea", "title"];
+
+/// Elements that aren't inside another element of the block, in order
+pub fn top_level_elements(html: &str) -> Vec<HtmlElement> {
+    let bytes = html.as_bytes();
+    let mut elements = vec![];
+    // Names of the open elements, the first one is the top-level element started at `start`
+    let mut open: Vec<String> = vec![];
+    let mut start = 0;
+    let mut name_end = 0;
+    let mut i = 0;
+    while let Some(found) = html[i..].find('<') {
+        let at = i + found;
+        let rest = &html[at..];
+        if rest.starts_with("<!--") {
+            i = skip_past(html, at + 4, "-->");
+        } else if rest.starts_with("<![CDATA[") {
+            i = skip_past(html, at + 9, "]]>");
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            i = skip_past(html, at + 2, ">");
+        } else if let Some(name) = tag_name(&html[at + 1..]) {
+            let after_name = at + 1 + name.len();
+            let (tag_end, self_closing) = tag_end(bytes, after_name);
+            let name = name.to_ascii_lowercase();
+            if open.is_empty() {
+                start = at;
+                name_end = after_name;
+            }
+            i = tag_end;
+            if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+                if open.is_empty() {
+                    elements.push(HtmlElement {
+                        start,
+                        name_end,
+                        end: tag_end,
+                    });
+                }
+                continue;
+            }
+            if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                // Its closing tag is handled by the next iteration
+                i = find_closing_tag(html, tag_end, &name);
+            }
+            open.push(name);
+        } else if let Some(name) = rest.strip_prefix("</").and_then(tag_name) {
+            let name = name.to_ascii_lowercase();
+            let (tag_end, _) = tag_end(bytes, at + 2 + name.len());
+            i = tag_end;
+            // Closing an element closes the ones opened inside it
+            if let Some(index) = open.iter().rposition(|n| *n == name) {
+                open.truncate(index);
+                if open.is_empty() {
+                    elements.push(HtmlElement {
+                        start,
+                        name_end,
+                        end: tag_end,
+                    });
+                }
+            }
+        } else {
+            i = at + 1;
+        }
+    }
+    if !open.is_empty() {
+        elements.push(HtmlElement {
+            start,
+            name_end,
+            end: html.len(),
+        });
+    }
+    elements
+}
+
+// Tag name at the beginning of `s`, custom elements have dashes
+fn tag_name(s: &str) -> Option<&str> {
+    if !s.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let len = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(s.len());
+    Some(&s[..len])
+}
+
+// End of the tag after its `>` and whether it ends with `/>`, `>` inside quoted values doesn't count
+fn tag_end(bytes: &[u8], from: usize) -> (usize, bool) {
+    let mut quote = None;
+    for (i, b) in bytes.iter().enumerate().skip(from) {
+        match (quote, b) {
+            (Some(q), _) if q == *b => quote = None,
+            (Some(_), _) => {}
+            (None, b'"' | b'\'') => quote = Some(*b),
+            (None, b'>') => return (i + 1, i > from && bytes[i - 1] == b'/'),
+            _ => {}
+        }
+    }
+    (bytes.len(), false)
+}
+
+fn skip_past(html: &str, from: usize, end: &str) -> usize {
+    html[from..]
+        .find(end)
+        .map_or(html.len(), |found| from + found + end.len())
+}
+
+fn find_closing_tag(html: &str, from: usize, name: &str) -> usize {
+    let closing = format!("</{}", name);
+    html[from..]
+        .to_ascii_lowercase()
+        .find(&closing)
+        .map_or(html.len(), |found| from + found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{top_level_elements, HtmlElement};
+
+    fn spans(html: &str) -> Vec<&str> {
+        top_level_elements(html)
+            .iter()
+            .map(|e| &html[e.start..e.end])
+            .collect()
+    }
+
+    #[test]
+    fn elements() {
+        let html = "<p>a <b>b</b></p>\n<img src=\"x\">\n<br/><hr>";
+        assert_eq!(
+            spans(html),
+            vec!["<p>a <b>b</b></p>", "<img src=\"x\">", "<br/>", "<hr>"]
+        );
+        assert_eq!(
+            top_level_elements(html)[0],
+            HtmlElement {
+                start: 0,
+                name_end: 2,
+                end: 17
+            }
+        );
+
+        // Comments, stray closing tags and text aren't elements
+        let html = "  <!-- <p>no</p> -->\n</p>\ntext <DIV class='a>b'>\n<div>x</div></Div>tail";
+        assert_eq!(spans(html), vec!["<DIV class='a>b'>\n<div>x</div></Div>"]);
+        assert_eq!(
+            spans("<!DOCTYPE html><?xml ?><![CDATA[<p>]]>"),
+            Vec::<&str>::new()
+        );
+
+        // Nested elements of the same name and unclosed ones
+        assert_eq!(
+            spans("<div><div></div></div><my-card>\n<p>open"),
+            vec!["<div><div></div></div>", "<my-card>\n<p>open"]
+        );
+        // A closing tag closes the elements opened inside it
+        assert_eq!(spans("<div><p>a</div><p>"), vec!["<div><p>a</div>", "<p>"]);
+
+        // Tags in scripts are text
+        assert_eq!(
+            spans("<script>let a = '</div><p>';</script><p>b</p>"),
+            vec!["<script>let a = '</div><p>';</script>", "<p>b</p>"]
+        );
+        assert_eq!(spans("a < b <"), Vec::<&str>::new());
+    }
+}
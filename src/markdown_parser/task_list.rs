@@ -0,0 +1,218 @@
+//! GFM task lists (`- [ ] todo` and `- [x] done`)
+use markdown_it::common::sourcemap::SourcePos;
+use markdown_it::parser::core::CoreRule;
+use markdown_it::parser::inline::builtin::InlineParserRule;
+use markdown_it::parser::inline::Text;
+use markdown_it::plugins::cmark::block::list::{BulletList, ListItem, OrderedList};
+use markdown_it::plugins::cmark::block::paragraph::Paragraph;
+use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+
+#[derive(Debug)]
+pub struct TaskCheckbox {
+    pub checked: bool,
+}
+
+impl NodeValue for TaskCheckbox {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("type", "checkbox".into()));
+        attrs.push(("class", "task-list-item-checkbox".into()));
+        if self.checked {
+            attrs.push(("checked", "".into()));
+        }
+        fmt.self_close("input", &attrs);
+        fmt.text(" ");
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    md.add_rule::<TaskListRule>().after::<InlineParserRule>();
+}
+
+#[doc(hidden)]
+pub struct TaskListRule;
+impl CoreRule for TaskListRule {
+    fn run(root: &mut Node, _: &MarkdownIt) {
+        root.walk_mut(|node, _| {
+            if !node.is::<BulletList>() && !node.is::<OrderedList>() {
+                return;
+            }
+            let mut has_tasks = false;
+            for item in node.children.iter_mut() {
+                if item.is::<ListItem>() && convert_list_item(item) {
+                    item.attrs.push(("class", "task-list-item".into()));
+                    has_tasks = true;
+                }
+            }
+            if has_tasks {
+                node.attrs.push(("class", "contains-task-list".into()));
+            }
+        });
+    }
+}
+
+// Tight lists keep inline content directly in the list item,
+// loose lists wrap it into a paragraph
+fn convert_list_item(item: &mut Node) -> bool {
+    let inline_parent = match item.children.first() {
+        Some(first) if first.is::<Paragraph>() => &mut item.children[0],
+        Some(_) => item,
+        None => return false,
+    };
+
+    let Some(text_node) = inline_parent.children.first_mut() else {
+        return false;
+    };
+    let Some(text) = text_node.cast_mut::<Text>() else {
+        return false;
+    };
+    let checked = match text.content.get(..3) {
+        Some("[ ]") => false,
+        Some("[x]") | Some("[X]") => true,
+        _ => return false,
+    };
+    let marker = match text.content[3..].chars().next() {
+        None => 3,
+        Some(' ') | Some('\t') => 4,
+        Some(_) => return false,
+    };
+    text.content = text.content[marker..].to_string();
+
+    let mut checkbox = Node::new(TaskCheckbox { checked });
+    if let Some(map) = text_node.srcmap {
+        let (start, end) = map.get_byte_offsets();
+        checkbox.srcmap = Some(SourcePos::new(start, start + 3));
+        text_node.srcmap = Some(SourcePos::new(start + marker, end));
+    }
+    inline_parent.children.insert(0, checkbox);
+    true
+}
+
+/// Flips `[ ]` <-> `[x]` of the task list item that starts at `line` (1-based).
+/// Returns the updated source and the new state of the checkbox.
+pub fn toggle_task(source: &str, line: u32) -> Option<(String, bool)> {
+    let mut offset = 0;
+    let src_line = source
+        .split_inclusive('\n')
+        .inspect(|l| offset += l.len())
+        .nth(line.checked_sub(1)? as usize)?;
+    let line_start = offset - src_line.len();
+
+    let bytes = src_line.as_bytes();
+    let mut pos = 0;
+    // blockquote markers and indentation
+    while pos < bytes.len() && matches!(bytes[pos], b' ' | b'\t' | b'>') {
+        pos += 1;
+    }
+    // list marker
+    match bytes.get(pos)? {
+        b'-' | b'*' | b'+' => pos += 1,
+        b'0'..=b'9' => {
+            while bytes.get(pos)?.is_ascii_digit() {
+                pos += 1;
+            }
+            if !matches!(bytes.get(pos)?, b'.' | b')') {
+                return None;
+            }
+            pos += 1;
+        }
+        _ => return None,
+    }
+    if !matches!(bytes.get(pos)?, b' ' | b'\t') {
+        return None;
+    }
+    while matches!(bytes.get(pos)?, b' ' | b'\t') {
+        pos += 1;
+    }
+
+    if bytes.get(pos)? != &b'[' || bytes.get(pos + 2)? != &b']' {
+        return None;
+    }
+    let (replacement, checked) = match bytes[pos + 1] {
+        b' ' => ("x", true),
+        b'x' | b'X' => (" ", false),
+        _ => return None,
+    };
+
+    let mark = line_start + pos + 1;
+    let mut result = String::with_capacity(source.len());
+    result.push_str(&source[..mark]);
+    result.push_str(replacement);
+    result.push_str(&source[mark + 1..]);
+    Some((result, checked))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::toggle_task;
+    use crate::markdown_parser::MarkdownParser;
+
+    #[test]
+    fn tight_task_list() {
+        let parser = &mut MarkdownParser::new();
+        let src = "- [ ] todo\n- [x] done\n- not a task";
+        let html = parser.parse(src).render();
+        assert_eq!(
+            html,
+            r#"<ul class="contains-task-list" data-spos="1-3">
+<li class="task-list-item" data-spos="1-1"><input data-spos="1-1" type="checkbox" class="task-list-item-checkbox"> todo</li>
+<li class="task-list-item" data-spos="2-2"><input data-spos="2-2" type="checkbox" class="task-list-item-checkbox" checked=""> done</li>
+<li data-spos="3-3">not a task</li>
+</ul>
+"#
+        );
+
+        // The task text starts after the checkbox
+        parser.set_inline_positions(true);
+        assert_eq!(
+            parser.parse(src).render(),
+            r#"<ul class="contains-task-list" data-spos="1-3">
+<li class="task-list-item" data-spos="1-1"><input data-spos="1:3-1:5" type="checkbox" class="task-list-item-checkbox"> <span data-spos="1:7-1:10">todo</span></li>
+<li class="task-list-item" data-spos="2-2"><input data-spos="2:3-2:5" type="checkbox" class="task-list-item-checkbox" checked=""> <span data-spos="2:7-2:10">done</span></li>
+<li data-spos="3-3"><span data-spos="3:3-3:12">not a task</span></li>
+</ul>
+"#
+        );
+    }
+
+    #[test]
+    fn loose_task_list() {
+        let parser = &mut MarkdownParser::new();
+        let html = parser.parse("1. [X] first\n\n2. [] second").render();
+        assert_eq!(
+            html,
+            r#"<ol class="contains-task-list" data-spos="1-3">
+<li class="task-list-item" data-spos="1-2">
+<p data-spos="1-1"><input data-spos="1-1" type="checkbox" class="task-list-item-checkbox" checked=""> first</p>
+</li>
+<li data-spos="3-3">
+<p data-spos="3-3">[] second</p>
+</li>
+</ol>
+"#
+        );
+    }
+
+    #[test]
+    fn toggle_task_in_source() {
+        let src = "# Todo\n\n- [ ] one\n  > 1. [x] two\n- [] three\n";
+        assert_eq!(
+            toggle_task(src, 3).unwrap(),
+            (
+                "# Todo\n\n- [x] one\n  > 1. [x] two\n- [] three\n".to_string(),
+                true
+            )
+        );
+        assert_eq!(
+            toggle_task(src, 4).unwrap(),
+            (
+                "# Todo\n\n- [ ] one\n  > 1. [ ] two\n- [] three\n".to_string(),
+                false
+            )
+        );
+        assert_eq!(toggle_task(src, 1), None);
+        assert_eq!(toggle_task(src, 5), None);
+        assert_eq!(toggle_task(src, 0), None);
+        assert_eq!(toggle_task(src, 42), None);
+    }
+}
@@ -0,0 +1,184 @@
+//! Expands `[TOC]` and `<!-- toc -->` markers into a nested list of heading links
+use super::heading_anchor::{Heading, HeadingAnchorRule, HeadingsExt};
+use markdown_it::parser::core::{CoreRule, Root};
+use markdown_it::parser::extset::MarkdownItExt;
+use markdown_it::parser::inline::Text;
+use markdown_it::plugins::cmark::block::paragraph::Paragraph;
+use markdown_it::plugins::html::html_block::HtmlBlock;
+use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+
+/// Range of heading levels listed in the expanded table of contents
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TocDepth {
+    pub min: u8,
+    pub max: u8,
+}
+
+impl Default for TocDepth {
+    fn default() -> Self {
+        TocDepth { min: 1, max: 6 }
+    }
+}
+
+impl MarkdownItExt for TocDepth {}
+
+#[derive(Debug)]
+pub struct TableOfContents {
+    pub headings: Vec<Heading>,
+}
+
+impl NodeValue for TableOfContents {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        if self.headings.is_empty() {
+            return;
+        }
+        let mut attrs = vec![("class", "table-of-contents".to_string())];
+        attrs.extend(node.attrs.iter().cloned());
+        fmt.cr();
+        fmt.open("ul", &attrs);
+        fmt.cr();
+
+        // Levels of the currently open lists, the deepest one is the last
+        let mut levels: Vec<u8> = vec![];
+        for heading in self.headings.iter() {
+            match levels.last() {
+                None => levels.push(heading.level),
+                Some(&last) if heading.level > last => {
+                    fmt.cr();
+                    fmt.open("ul", &[]);
+                    fmt.cr();
+                    levels.push(heading.level);
+                }
+                Some(_) => {
+                    fmt.close("li");
+                    fmt.cr();
+                    while levels.len() > 1 && levels[levels.len() - 2] >= heading.level {
+                        levels.pop();
+                        fmt.close("ul");
+                        fmt.cr();
+                        fmt.close("li");
+                        fmt.cr();
+                    }
+                    *levels.last_mut().unwrap() = heading.level;
+                }
+            }
+            fmt.open("li", &[]);
+            fmt.open("a", &[("href", format!("#{}", heading.slug))]);
+            fmt.text(&heading.title);
+            fmt.close("a");
+        }
+        fmt.close("li");
+        fmt.cr();
+        for _ in 1..levels.len() {
+            fmt.close("ul");
+            fmt.cr();
+            fmt.close("li");
+            fmt.cr();
+        }
+
+        fmt.close("ul");
+        fmt.cr();
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    md.add_rule::<TocPlaceholderRule>()
+        .after::<HeadingAnchorRule>();
+}
+
+fn is_placeholder(node: &Node) -> bool {
+    if let Some(html) = node.cast::<HtmlBlock>() {
+        let content = html.content.trim();
+        return content
+            .strip_prefix("<!--")
+            .and_then(|c| c.strip_suffix("-->"))
+            .is_some_and(|c| c.trim().eq_ignore_ascii_case("toc"));
+    }
+    if node.is::<Paragraph>() && node.children.len() == 1 {
+        if let Some(text) = node.children[0].cast::<Text>() {
+            return text.content.trim().eq_ignore_ascii_case("[toc]");
+        }
+    }
+    false
+}
+
+#[doc(hidden)]
+pub struct TocPlaceholderRule;
+impl CoreRule for TocPlaceholderRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let depth = md.ext.get::<TocDepth>().copied().unwrap_or_default();
+        let headings: Vec<Heading> = match root.cast::<Root>().unwrap().ext.get::<HeadingsExt>() {
+            Some(ext) => ext
+                .headings
+                .iter()
+                .filter(|h| h.level >= depth.min && h.level <= depth.max)
+                .cloned()
+                .collect(),
+            None => return,
+        };
+
+        root.walk_mut(|node, _| {
+            if is_placeholder(node) {
+                node.children.clear();
+                node.replace(TableOfContents {
+                    headings: headings.clone(),
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TocDepth;
+    use crate::markdown_parser::MarkdownParser;
+
+    #[test]
+    fn toc_placeholder() {
+        let parser = &mut MarkdownParser::new();
+        let html = parser
+            .parse("[TOC]\n\n# One\n### Deep\n## Two\n# Three\n\n<!-- toc -->\n")
+            .render();
+        let toc = r##"<ul class="table-of-contents" data-spos="1-1">
+<li><a href="#one">One</a>
+<ul>
+<li><a href="#deep">Deep</a></li>
+<li><a href="#two">Two</a></li>
+</ul>
+</li>
+<li><a href="#three">Three</a></li>
+</ul>
+"##;
+        assert!(html.starts_with(toc));
+        assert!(html.ends_with(&toc.replace("1-1", "8-8")));
+    }
+
+    #[test]
+    fn toc_depth() {
+        let parser = &mut MarkdownParser::new();
+        parser.set_toc_depth(TocDepth { min: 2, max: 3 });
+        let html = parser
+            .parse("# Title\n<!--TOC-->\n## One\n#### Too deep\n### Deep\n## Two")
+            .render();
+        assert!(html.contains(
+            r##"<ul class="table-of-contents" data-spos="2-2">
+<li><a href="#one">One</a>
+<ul>
+<li><a href="#deep">Deep</a></li>
+</ul>
+</li>
+<li><a href="#two">Two</a></li>
+</ul>
+"##
+        ));
+    }
+
+    #[test]
+    fn not_a_placeholder() {
+        let parser = &mut MarkdownParser::new();
+        let html = parser
+            .parse("# Title\n\nSee [TOC] below\n\n<!-- toc --><br>")
+            .render();
+        assert!(!html.contains("table-of-contents"));
+    }
+}
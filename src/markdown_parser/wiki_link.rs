@@ -0,0 +1,215 @@
+//! Obsidian-style `[[Page Name]]`, `[[page|alias]]` and `[[Page#Heading]]` links
+use super::heading_anchor::slugify;
+use markdown_it::parser::extset::MarkdownItExt;
+use markdown_it::parser::inline::{InlineRule, InlineState, Text};
+use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Pages wiki links are resolved against, keyed by the lowercased
+/// path relative to the wiki root and by the lowercased file name, both without extension
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WikiPages {
+    pages: HashMap<String, PathBuf>,
+}
+
+impl MarkdownItExt for WikiPages {}
+
+impl WikiPages {
+    pub fn insert(&mut self, relative: &Path, path: PathBuf) {
+        let key = relative
+            .with_extension("")
+            .to_string_lossy()
+            .replace('\\', "/")
+            .to_lowercase();
+        if let Some(stem) = relative.file_stem() {
+            // The first page with the given name wins, like in Obsidian
+            self.pages
+                .entry(stem.to_string_lossy().to_lowercase())
+                .or_insert_with(|| path.clone());
+        }
+        self.pages.insert(key, path);
+    }
+
+    pub fn resolve(&self, page: &str) -> Option<&PathBuf> {
+        let page = page.trim().trim_start_matches('/').to_lowercase();
+        let page = page.strip_suffix(".md").unwrap_or(&page);
+        self.pages.get(page)
+    }
+}
+
+#[derive(Debug)]
+pub struct WikiLink {
+    /// Page name as written in the link, empty for `[[#Heading]]`
+    pub page: String,
+    pub fragment: Option<String>,
+    /// `None` when the page doesn't exist in the wiki
+    pub path: Option<PathBuf>,
+}
+
+impl WikiLink {
+    pub fn href(&self) -> Option<String> {
+        let fragment = self.fragment.as_deref().map(slugify);
+        match (&self.path, fragment) {
+            (Some(path), Some(fragment)) => Some(format!("{}#{}", path.display(), fragment)),
+            (Some(path), None) => Some(path.display().to_string()),
+            (None, Some(fragment)) if self.page.is_empty() => Some(format!("#{}", fragment)),
+            _ => None,
+        }
+    }
+}
+
+impl NodeValue for WikiLink {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        match self.href() {
+            Some(href) => {
+                attrs.push(("class", "wiki-link".into()));
+                attrs.push(("href", href));
+                if let Some(path) = &self.path {
+                    attrs.push(("data-wiki-path", path.display().to_string()));
+                }
+                fmt.open("a", &attrs);
+                fmt.contents(&node.children);
+                fmt.close("a");
+            }
+            None => {
+                attrs.push(("class", "wiki-link wiki-link-new".into()));
+                attrs.push(("title", format!("{} doesn't exist", self.page)));
+                fmt.open("span", &attrs);
+                fmt.contents(&node.children);
+                fmt.close("span");
+            }
+        }
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    md.inline.add_rule::<WikiLinkScanner>().before_all();
+}
+
+/// Parser for `link_targets`, built once and reused for every file
+pub fn link_parser() -> MarkdownIt {
+    let mut md = MarkdownIt::new();
+    markdown_it::plugins::cmark::add(&mut md);
+    add(&mut md);
+    md
+}
+
+/// Targets of all wiki links in the source, e.g. `[[Page#Heading|alias]]` -> `Page`
+pub fn link_targets(md: &MarkdownIt, src: &str) -> Vec<String> {
+    let mut targets = vec![];
+    md.parse(src).walk(|node, _| {
+        if let Some(link) = node.cast::<WikiLink>() {
+            if !link.page.is_empty() {
+                targets.push(link.page.clone());
+            }
+        }
+    });
+    targets
+}
+
+#[doc(hidden)]
+pub struct WikiLinkScanner;
+impl InlineRule for WikiLinkScanner {
+    const MARKER: char = '[';
+
+    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+        let input = &state.src[state.pos..state.pos_max];
+        let inner = input.strip_prefix("[[")?;
+        let end = inner.find("]]")?;
+        let inner = &inner[..end];
+        if inner.trim().is_empty() || inner.contains(['[', '\n']) {
+            return None;
+        }
+
+        let (target, alias) = match inner.split_once('|') {
+            Some((target, alias)) => (target.trim(), Some(alias.trim())),
+            None => (inner.trim(), None),
+        };
+        let (page, fragment) = match target.split_once('#') {
+            Some((page, fragment)) => (page.trim(), Some(fragment.trim().to_string())),
+            None => (target, None),
+        };
+        let path = state
+            .md
+            .ext
+            .get::<WikiPages>()
+            .and_then(|pages| pages.resolve(page))
+            .cloned();
+
+        let mut node = Node::new(WikiLink {
+            page: page.to_string(),
+            fragment,
+            path,
+        });
+        node.children.push(Node::new(Text {
+            content: alias
+                .filter(|a| !a.is_empty())
+                .unwrap_or(target)
+                .to_string(),
+        }));
+        Some((node, end + 4))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{link_parser, link_targets, WikiPages};
+    use crate::markdown_parser::MarkdownParser;
+    use std::path::{Path, PathBuf};
+
+    fn pages() -> WikiPages {
+        let mut pages = WikiPages::default();
+        for page in ["Home.md", "notes/Page Name.md", "archive/Page Name.md"] {
+            pages.insert(Path::new(page), Path::new("/wiki").join(page));
+        }
+        pages
+    }
+
+    #[test]
+    fn resolve_pages() {
+        let pages = pages();
+        let path = |p: &str| Some(PathBuf::from(p));
+        assert_eq!(pages.resolve("home").cloned(), path("/wiki/Home.md"));
+        assert_eq!(
+            pages.resolve("Page Name").cloned(),
+            path("/wiki/notes/Page Name.md")
+        );
+        assert_eq!(
+            pages.resolve("archive/page name.md").cloned(),
+            path("/wiki/archive/Page Name.md")
+        );
+        assert_eq!(pages.resolve("Missing"), None);
+    }
+
+    #[test]
+    fn wiki_links() {
+        let parser = &mut MarkdownParser::new();
+        parser.set_wiki_pages(pages());
+        let html = parser
+            .parse("[[Home]], [[page name|the page]], [[Home#Getting started]], [[#Local]] and [[Missing]]")
+            .render();
+        assert_eq!(
+            html,
+            r##"<p data-spos="1-1"><a data-spos="1-1" class="wiki-link" href="/wiki/Home.md" data-wiki-path="/wiki/Home.md">Home</a>, <a data-spos="1-1" class="wiki-link" href="/wiki/notes/Page Name.md" data-wiki-path="/wiki/notes/Page Name.md">the page</a>, <a data-spos="1-1" class="wiki-link" href="/wiki/Home.md#getting-started" data-wiki-path="/wiki/Home.md">Home#Getting started</a>, <a data-spos="1-1" class="wiki-link" href="#local">#Local</a> and <span data-spos="1-1" class="wiki-link wiki-link-new" title="Missing doesn't exist">Missing</span></p>
+"##
+        );
+    }
+
+    #[test]
+    fn not_wiki_links() {
+        let parser = &mut MarkdownParser::new();
+        let html = parser
+            .parse("[[]] [[a\nb]] [link](url) `[[code]]`")
+            .render();
+        assert!(!html.contains("wiki-link"));
+        assert_eq!(
+            link_targets(
+                &link_parser(),
+                "[[One]] `[[Two]]`\n\n    [[Three]]\n\n[[Four|4]] [[#Five]]"
+            ),
+            vec!["One", "Four"]
+        );
+    }
+}
@@ -0,0 +1,646 @@
+// Loaded once into the page head, handles interactions with the rendered markdown
+pub const PREVIEW_SCRIPT: &str = r##"
+// Message channel to Rust, the messages are described in channel.rs
+const crabix = (function () {
+  const VERSION = 2;
+  // Messages are delivered before the render they belong to, the DOM is updated after
+  const RENDER_DELAY = 100;
+  const handlers = {};
+  const deferred = new Set();
+  const outbox = [];
+
+  // The bridge input is rendered by dioxus after the script is loaded
+  function flush() {
+    const bridge = document.getElementById('crabix-channel');
+    if (bridge === null) {
+      setTimeout(flush, 50);
+      return;
+    }
+    while (outbox.length > 0) {
+      bridge.value = JSON.stringify(outbox.shift());
+      bridge.dispatchEvent(new Event('input', { bubbles: true }));
+    }
+  }
+
+  function send(message) {
+    outbox.push(message);
+    if (outbox.length === 1) {
+      flush();
+    }
+  }
+
+  function receive(message) {
+    const handler = handlers[message.type];
+    if (handler === undefined) {
+      console.warn('Unknown message', message);
+      return;
+    }
+    if (deferred.has(message.type)) {
+      setTimeout(function () { handler(message); }, RENDER_DELAY);
+    } else {
+      handler(message);
+    }
+  }
+
+  // `afterRender` handlers wait for the DOM of the render, e.g. to scroll to its elements
+  function on(type, handler, afterRender) {
+    handlers[type] = handler;
+    if (afterRender) {
+      deferred.add(type);
+    }
+  }
+
+  send({ type: 'ready', version: VERSION });
+  return { send: send, receive: receive, on: on };
+})();
+
+// Live updates only replace the blocks that changed, see blocks.rs.
+// The nodes of each rendered block are kept on the markdown body with the generation they show.
+function patchBlocks(message) {
+  const body = document.querySelector('.markdown-body');
+  if (body === null) {
+    setTimeout(function () { patchBlocks(message); }, 50);
+    return;
+  }
+  const old = message.base === null ? [] : body.crabixBlocks;
+  const attached = function (nodes) {
+    return nodes.every(function (node) { return node.parentNode === body; });
+  };
+  if (message.base !== null && (body.crabixGeneration !== message.base || !old.every(attached))) {
+    crabix.send({ type: 'resync' });
+    return;
+  }
+  if (message.base === null) {
+    body.replaceChildren();
+  }
+
+  const blocks = [];
+  let pending = [];
+  let next = 0;
+  const drop = function (until) {
+    for (; next < until; next++) {
+      old[next].forEach(function (node) { node.remove(); });
+    }
+  };
+  const place = function (before) {
+    pending.forEach(function (node) { body.insertBefore(node, before); });
+    pending = [];
+  };
+  for (const patch of message.blocks) {
+    if (patch.op === 'insert') {
+      const template = document.createElement('template');
+      template.innerHTML = patch.html;
+      const nodes = Array.from(template.content.childNodes);
+      pending.push(...nodes);
+      blocks.push(nodes);
+      continue;
+    }
+    drop(patch.index);
+    next = patch.index + 1;
+    const nodes = old[patch.index];
+    if (nodes.length > 0) {
+      place(nodes[0]);
+    }
+    if (patch.shift !== 0) {
+      shiftSpos(nodes, patch.shift);
+    }
+    blocks.push(nodes);
+  }
+  drop(old.length);
+  place(null);
+  body.crabixBlocks = blocks;
+  body.crabixGeneration = message.generation;
+}
+crabix.on('patch', patchBlocks);
+
+// Source lines of a block moved by an edit above it
+function shiftSpos(nodes, shift) {
+  for (const node of nodes) {
+    if (node.nodeType !== Node.ELEMENT_NODE) {
+      continue;
+    }
+    const elements = Array.from(node.querySelectorAll('[data-spos]'));
+    if (node.hasAttribute('data-spos')) {
+      elements.push(node);
+    }
+    // Blocks have `START-END` lines, inline elements `LINE:COLUMN-LINE:COLUMN`
+    for (const element of elements) {
+      element.dataset.spos = element.dataset.spos.split('-').map(function (position) {
+        const parts = position.split(':');
+        parts[0] = Number(parts[0]) + shift;
+        return parts.join(':');
+      }).join('-');
+    }
+  }
+}
+
+function sposElement(spos) {
+  return document.querySelector(`[data-spos='${spos.start_line}-${spos.end_line}']`);
+}
+
+function inlineSpos(element) {
+  return element.dataset.spos.split('-').map(function (position) {
+    return position.split(':').map(Number);
+  });
+}
+
+// Innermost inline element of the block at the source position,
+// otherwise the closest one on the same line
+function inlineElement(block, line, column) {
+  let found = null;
+  let nearest = null;
+  let distance = Infinity;
+  for (const element of block.querySelectorAll('[data-spos*=":"]')) {
+    const [start, end] = inlineSpos(element);
+    const afterStart = start[0] < line || (start[0] === line && start[1] <= column);
+    const beforeEnd = end[0] > line || (end[0] === line && end[1] >= column);
+    if (afterStart && beforeEnd) {
+      // Descendants come after their ancestors
+      found = element;
+    } else if (start[0] === line || end[0] === line) {
+      const d = Math.min(
+        start[0] === line ? Math.abs(start[1] - column) : Infinity,
+        end[0] === line ? Math.abs(end[1] - column) : Infinity
+      );
+      if (d < distance) {
+        nearest = element;
+        distance = d;
+      }
+    }
+  }
+  return found || nearest;
+}
+
+// Range of the word at the column in an element holding only text
+function wordRange(element, line, column) {
+  const text = element.firstChild;
+  const [start] = inlineSpos(element);
+  if (element.childNodes.length !== 1 || text.nodeType !== Node.TEXT_NODE || start[0] !== line) {
+    return null;
+  }
+  // Columns count characters, offsets UTF-16 code units
+  const chars = Array.from(text.data);
+  if (chars.length === 0) {
+    return null;
+  }
+  const at = Math.min(Math.max(column - start[1], 0), chars.length - 1);
+  let from = at;
+  let to = at + 1;
+  while (from > 0 && !/\s/.test(chars[from - 1])) {
+    from--;
+  }
+  while (to < chars.length && !/\s/.test(chars[to])) {
+    to++;
+  }
+  const offset = function (index) { return chars.slice(0, index).join('').length; };
+  const range = document.createRange();
+  range.setStart(text, offset(from));
+  range.setEnd(text, offset(to));
+  return range;
+}
+
+// Window rectangle of the word at the editor cursor, null without a column
+function cursorRect(block, message) {
+  if (message.column === null) {
+    return null;
+  }
+  const element = inlineElement(block, message.line, message.column);
+  if (element === null) {
+    return null;
+  }
+  const range = wordRange(element, message.line, message.column);
+  return (range || element).getBoundingClientRect();
+}
+
+// Live scrolling to the block at the editor cursor, or to the word when the column is known
+crabix.on('scroll_to', function (message) {
+  const element = sposElement(message.spos);
+  if (element === null) {
+    return;
+  }
+  const word = cursorRect(element, message);
+  if (message.reveal_only && (word === null || (word.top >= 0 && word.bottom <= window.innerHeight))) {
+    return;
+  }
+  const rect = element.getBoundingClientRect();
+  const target = word === null
+    ? rect.top + rect.height * message.block_fraction
+    : word.top + word.height / 2;
+  window.scrollTo({
+    top: target + window.pageYOffset - window.innerHeight * message.anchor,
+    left: 0,
+    behavior: message.smooth ? 'smooth' : 'auto'
+  });
+}, true);
+
+crabix.on('highlight', function (message) {
+  for (const focused of document.querySelectorAll('.crabix-focus')) {
+    focused.classList.remove('crabix-focus');
+  }
+  for (const word of document.querySelectorAll('.crabix-focus-word')) {
+    word.remove();
+  }
+  const element = message.spos === null ? null : sposElement(message.spos);
+  if (element === null) {
+    return;
+  }
+  element.classList.add('crabix-focus');
+  // The word is marked over the text, wrapping it in an element would change the layout
+  const rect = document.querySelector('.focus-bar, .focus-tint') === null ? null : cursorRect(element, message);
+  if (rect !== null) {
+    const mark = document.createElement('div');
+    mark.className = 'crabix-focus-word';
+    mark.style.top = `${rect.top + window.pageYOffset}px`;
+    mark.style.left = `${rect.left + window.pageXOffset}px`;
+    mark.style.width = `${rect.width}px`;
+    mark.style.height = `${rect.height}px`;
+    document.body.appendChild(mark);
+  }
+}, true);
+
+crabix.on('scroll_to_fragment', function (message) {
+  const target = document.getElementById(message.id);
+  if (target !== null) {
+    target.scrollIntoView();
+  }
+}, true);
+
+crabix.on('scroll_to_offset', function (message) {
+  window.scrollTo(0, message.offset);
+}, true);
+
+// The palette is rendered after the message, its input is waited for a few frames
+function focusCommandPalette(frames) {
+  const input = document.querySelector('.command-palette input');
+  if (input !== null) {
+    input.focus();
+  } else if (frames > 0) {
+    requestAnimationFrame(function () { focusCommandPalette(frames - 1); });
+  }
+}
+
+crabix.on('focus_command_palette', function () { focusCommandPalette(10); });
+
+document.addEventListener('click', function (event) {
+  const checkbox = event.target.closest('.task-list-item-checkbox');
+  if (checkbox === null) {
+    return;
+  }
+  // The checkbox state is restored from the updated markdown source
+  event.preventDefault();
+  crabix.send({ type: 'toggle_task', line: parseInt(checkbox.dataset.spos, 10) });
+}, true);
+
+function copyToClipboard(text) {
+  if (navigator.clipboard !== undefined) {
+    navigator.clipboard.writeText(text);
+    return;
+  }
+  const area = document.createElement('textarea');
+  area.value = text;
+  document.body.appendChild(area);
+  area.select();
+  document.execCommand('copy');
+  area.remove();
+}
+
+// In-document links are handled here, dioxus opens every other link in the system browser
+document.addEventListener('click', function (event) {
+  const link = event.target.closest('a[href^="#"]');
+  if (link === null) {
+    return;
+  }
+  event.preventDefault();
+  event.stopPropagation();
+  const fragment = link.getAttribute('href');
+  const target = document.getElementById(decodeURIComponent(fragment.slice(1)));
+  if (target !== null) {
+    target.scrollIntoView({ behavior: 'smooth', block: 'start' });
+  }
+  if (link.classList.contains('anchor')) {
+    copyToClipboard(fragment);
+  }
+}, true);
+
+// Wiki links are opened in Crabix instead of the system browser
+document.addEventListener('click', function (event) {
+  const link = event.target.closest('a[data-wiki-path]');
+  if (link === null) {
+    return;
+  }
+  event.preventDefault();
+  event.stopPropagation();
+  const path = link.dataset.wikiPath;
+  const fragment = link.getAttribute('href').slice(path.length);
+  crabix.send({ type: 'open_file', path: path, fragment: fragment.startsWith('#') ? fragment.slice(1) : null });
+}, true);
+
+// Highlights the table of contents entry of the section in the middle of the window
+let tocHighlightScheduled = false;
+function highlightTocEntry() {
+  tocHighlightScheduled = false;
+  const entries = document.querySelectorAll('.toc-entry');
+  if (entries.length === 0) {
+    return;
+  }
+  const middle = window.innerHeight / 2;
+  let current = null;
+  for (const heading of document.querySelectorAll('.markdown-body [id]')) {
+    if (heading.getBoundingClientRect().top > middle) {
+      break;
+    }
+    current = heading.id;
+  }
+  for (const entry of entries) {
+    entry.classList.toggle('current', entry.dataset.slug === current);
+  }
+}
+window.addEventListener('scroll', function () {
+  if (!tocHighlightScheduled) {
+    tocHighlightScheduled = true;
+    window.requestAnimationFrame(highlightTocEntry);
+  }
+});
+
+// Reports where the document is scrolled to once scrolling stops, it is restored on the next launch
+let scrollReportTimeout = null;
+window.addEventListener('scroll', function () {
+  clearTimeout(scrollReportTimeout);
+  scrollReportTimeout = setTimeout(function () {
+    crabix.send({ type: 'scroll', offset: Math.round(window.scrollY) });
+  }, 300);
+});
+
+// Find bar (Ctrl+F), matches in the rendered markdown are wrapped in <mark class="find-match">
+const crabixFind = (function () {
+  let bar = null;
+  let input, counter, caseSensitive, regex;
+  let matches = [];
+  let current = -1;
+  let scheduled = false;
+
+  // Live updates replace the rendered markdown, the matches are highlighted again
+  const observer = new MutationObserver(function (records) {
+    const body = document.querySelector('.markdown-body');
+    const rendered = records.some(function (record) {
+      return body === null || body.contains(record.target) || record.target.contains(body);
+    });
+    if (rendered && !scheduled && bar !== null && !bar.hidden) {
+      scheduled = true;
+      window.requestAnimationFrame(function () {
+        scheduled = false;
+        highlight(false);
+      });
+    }
+  });
+
+  function create() {
+    bar = document.createElement('div');
+    bar.className = 'find-bar';
+    bar.hidden = true;
+    bar.innerHTML = '<input class="find-input" type="text" placeholder="Find">'
+      + '<span class="find-count"></span>'
+      + '<label title="Match case"><input class="find-case" type="checkbox">Aa</label>'
+      + '<label title="Regular expression"><input class="find-regex" type="checkbox">.*</label>'
+      + '<button class="find-previous" title="Previous match (Shift+Enter)">↑</button>'
+      + '<button class="find-next" title="Next match (Enter)">↓</button>'
+      + '<button class="find-close" title="Close (Escape)">✕</button>';
+    document.body.appendChild(bar);
+    input = bar.querySelector('.find-input');
+    counter = bar.querySelector('.find-count');
+    caseSensitive = bar.querySelector('.find-case');
+    regex = bar.querySelector('.find-regex');
+
+    input.addEventListener('input', function () { highlight(true); });
+    caseSensitive.addEventListener('change', function () { highlight(true); });
+    regex.addEventListener('change', function () { highlight(true); });
+    input.addEventListener('keydown', function (event) {
+      if (event.key === 'Enter') {
+        event.preventDefault();
+        step(event.shiftKey ? -1 : 1);
+      } else if (event.key === 'Escape') {
+        event.preventDefault();
+        close();
+      }
+    });
+    bar.querySelector('.find-previous').addEventListener('click', function () { step(-1); });
+    bar.querySelector('.find-next').addEventListener('click', function () { step(1); });
+    bar.querySelector('.find-close').addEventListener('click', close);
+    observer.observe(document.body, { childList: true, subtree: true, characterData: true });
+  }
+
+  function pattern() {
+    if (input.value === '') {
+      return null;
+    }
+    const source = regex.checked ? input.value : input.value.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+    return new RegExp(source, caseSensitive.checked ? 'g' : 'gi');
+  }
+
+  function clear() {
+    for (const mark of document.querySelectorAll('mark.find-match')) {
+      const parent = mark.parentNode;
+      parent.replaceChild(document.createTextNode(mark.textContent), mark);
+      parent.normalize();
+    }
+    matches = [];
+  }
+
+  function highlight(reset) {
+    clear();
+    const body = document.querySelector('.markdown-body');
+    let search = null;
+    try {
+      search = pattern();
+      counter.classList.remove('error');
+    } catch (e) {
+      counter.textContent = 'Invalid regex';
+      counter.classList.add('error');
+    }
+    if (search !== null && body !== null) {
+      const walker = document.createTreeWalker(body, NodeFilter.SHOW_TEXT);
+      const nodes = [];
+      while (walker.nextNode()) {
+        nodes.push(walker.currentNode);
+      }
+      for (let node of nodes) {
+        const ranges = [];
+        for (const match of node.nodeValue.matchAll(search)) {
+          if (match[0].length > 0) {
+            ranges.push([match.index, match.index + match[0].length]);
+          }
+        }
+        let offset = 0;
+        for (const [start, end] of ranges) {
+          const text = node.splitText(start - offset);
+          node = text.splitText(end - start);
+          offset = end;
+          const mark = document.createElement('mark');
+          mark.className = 'find-match';
+          text.parentNode.replaceChild(mark, text);
+          mark.appendChild(text);
+          matches.push(mark);
+        }
+      }
+    }
+    // Our own changes must not trigger another highlight
+    observer.takeRecords();
+
+    if (reset) {
+      current = matches.length > 0 ? 0 : -1;
+    } else {
+      current = Math.min(current, matches.length - 1);
+      if (current < 0 && matches.length > 0) {
+        current = 0;
+      }
+    }
+    select(reset);
+  }
+
+  function select(scroll) {
+    matches.forEach(function (mark, i) {
+      mark.classList.toggle('current', i === current);
+    });
+    if (!counter.classList.contains('error')) {
+      if (input.value === '') {
+        counter.textContent = '';
+      } else if (matches.length === 0) {
+        counter.textContent = 'No results';
+      } else {
+        counter.textContent = (current + 1) + ' of ' + matches.length;
+      }
+    }
+    observer.takeRecords();
+    if (scroll && current >= 0) {
+      matches[current].scrollIntoView({ block: 'center' });
+    }
+  }
+
+  function step(direction) {
+    if (matches.length === 0) {
+      return;
+    }
+    current = (current + direction + matches.length) % matches.length;
+    select(true);
+  }
+
+  function open() {
+    if (bar === null) {
+      create();
+    }
+    bar.hidden = false;
+    input.focus();
+    input.select();
+    highlight(true);
+  }
+
+  function close() {
+    bar.hidden = true;
+    clear();
+    observer.takeRecords();
+  }
+
+  return { open: open, close: close, next: function () { step(1); }, previous: function () { step(-1); } };
+})();
+
+// Keyboard navigation and commands, the bindings are configured from the [keybindings] config section
+(function () {
+  // Time to type the next key of a sequence like `gg`
+  const SEQUENCE_TIMEOUT = 1000;
+  const SCROLL_STEP = 60;
+  let bindings = [];
+  let pending = [];
+  let timer = null;
+
+  function jumpToHeading(direction) {
+    const selector = [1, 2, 3, 4, 5, 6].map(function (level) {
+      return '.markdown-body h' + level + '[data-spos]';
+    }).join(', ');
+    const headings = Array.from(document.querySelectorAll(selector));
+    const target = direction > 0
+      ? headings.find(function (h) { return h.getBoundingClientRect().top > 1; })
+      : headings.reverse().find(function (h) { return h.getBoundingClientRect().top < -1; });
+    if (target !== undefined) {
+      target.scrollIntoView({ block: 'start' });
+    }
+  }
+
+  const actions = {
+    scroll_down: function () { window.scrollBy(0, SCROLL_STEP); },
+    scroll_up: function () { window.scrollBy(0, -SCROLL_STEP); },
+    half_page_down: function () { window.scrollBy(0, window.innerHeight / 2); },
+    half_page_up: function () { window.scrollBy(0, -window.innerHeight / 2); },
+    scroll_top: function () { window.scrollTo(0, 0); },
+    scroll_bottom: function () { window.scrollTo(0, document.body.scrollHeight); },
+    next_heading: function () { jumpToHeading(1); },
+    previous_heading: function () { jumpToHeading(-1); },
+    find: function () { crabixFind.open(); },
+  };
+
+  function run(command) {
+    if (actions[command] !== undefined) {
+      actions[command]();
+      return;
+    }
+    // The rest of the commands are handled by the application
+    crabix.send({ type: 'run_command', name: command });
+  }
+
+  // Same notation as the keybindings are normalized to in Rust
+  function keyName(event) {
+    if (['Control', 'Shift', 'Alt', 'Meta'].includes(event.key)) {
+      return null;
+    }
+    let name = '';
+    if (event.ctrlKey) { name += 'ctrl+'; }
+    if (event.altKey) { name += 'alt+'; }
+    if (event.metaKey) { name += 'meta+'; }
+    if (event.shiftKey && event.key.length > 1) { name += 'shift+'; }
+    return name + event.key;
+  }
+
+  function startsWith(keys, prefix) {
+    return prefix.every(function (key, i) { return keys[i] === key; });
+  }
+
+  document.addEventListener('keydown', function (event) {
+    const key = keyName(event);
+    if (key === null) {
+      return;
+    }
+    // Typing in the find bar or the search panel isn't navigation
+    const editable = event.target.closest('input, textarea, select, [contenteditable]') !== null;
+    if (editable && !(event.ctrlKey || event.altKey || event.metaKey)) {
+      return;
+    }
+    clearTimeout(timer);
+    pending.push(key);
+    let matching = bindings.filter(function (b) { return startsWith(b.keys, pending); });
+    if (matching.length === 0 && pending.length > 1) {
+      pending = [key];
+      matching = bindings.filter(function (b) { return startsWith(b.keys, pending); });
+    }
+    if (matching.length === 0) {
+      pending = [];
+      return;
+    }
+    event.preventDefault();
+    const exact = matching.find(function (b) { return b.keys.length === pending.length; });
+    if (exact !== undefined && matching.length === 1) {
+      pending = [];
+      run(exact.command);
+      return;
+    }
+    // Another binding starts with the typed keys, waits for the next key
+    timer = setTimeout(function () {
+      pending = [];
+      if (exact !== undefined) {
+        run(exact.command);
+      }
+    }, SEQUENCE_TIMEOUT);
+  });
+
+  crabix.on('configure', function (message) { bindings = message.keybindings.bindings; });
+  crabix.on('run_command', function (message) { run(message.name); });
+})();
+"##;
@@ -0,0 +1,345 @@
+use crate::markdown_parser::front_matter::FrontMatter;
+use crate::markdown_parser::toc_placeholder::TocDepth;
+use crate::markdown_parser::MarkdownParser;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub fn from_name(name: &str) -> Option<Theme> {
+        match name.to_lowercase().as_str() {
+            "light" => Some(Theme::Light),
+            "dark" => Some(Theme::Dark),
+            _ => None,
+        }
+    }
+
+    /// CSS class of the layout element
+    pub fn class(&self) -> &'static str {
+        match self {
+            Theme::Light => "theme-light",
+            Theme::Dark => "theme-dark",
+        }
+    }
+
+    /// Syntect theme for code blocks
+    pub fn code_theme(&self) -> &'static str {
+        match self {
+            Theme::Light => "InspiredGitHub",
+            Theme::Dark => "base16-ocean.dark",
+        }
+    }
+}
+
+/// Maximum width of the rendered markdown
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ContentWidth {
+    /// Like a README on GitHub
+    Github,
+    Wide,
+    #[default]
+    Full,
+}
+
+impl ContentWidth {
+    pub fn from_name(name: &str) -> Option<ContentWidth> {
+        match name.to_lowercase().as_str() {
+            "github" => Some(ContentWidth::Github),
+            "wide" => Some(ContentWidth::Wide),
+            "full" => Some(ContentWidth::Full),
+            _ => None,
+        }
+    }
+
+    fn max_width(&self) -> &'static str {
+        match self {
+            ContentWidth::Github => "980px",
+            ContentWidth::Wide => "1400px",
+            ContentWidth::Full => "none",
+        }
+    }
+}
+
+/// How the block at the editor cursor line is marked
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FocusHighlight {
+    #[default]
+    None,
+    /// Bar left of the block
+    Bar,
+    /// Background tint
+    Tint,
+}
+
+impl FocusHighlight {
+    pub fn from_name(name: &str) -> Option<FocusHighlight> {
+        match name.to_lowercase().as_str() {
+            "none" => Some(FocusHighlight::None),
+            "bar" => Some(FocusHighlight::Bar),
+            "tint" => Some(FocusHighlight::Tint),
+            _ => None,
+        }
+    }
+
+    /// CSS class of the layout element
+    pub fn class(&self) -> &'static str {
+        match self {
+            FocusHighlight::None => "",
+            FocusHighlight::Bar => "focus-bar",
+            FocusHighlight::Tint => "focus-tint",
+        }
+    }
+}
+
+/// CSS `font-family` list, e.g. `"Fira Code", monospace`. Empty is the built-in font.
+pub fn parse_font(value: &str) -> Result<Option<String>, String> {
+    if value.contains([';', '{', '}', '<', '>', '\\']) {
+        return Err(format!("invalid font \"{}\"", value));
+    }
+    Ok(Some(value.trim().to_string()).filter(|font| !font.is_empty()))
+}
+
+/// Size in pixels. Empty is the built-in size.
+pub fn parse_font_size(value: &str) -> Result<Option<u32>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.strip_suffix("px").unwrap_or(value).parse::<u32>() {
+        Ok(size) if (6..=72).contains(&size) => Ok(Some(size)),
+        _ => Err(format!(
+            "invalid font size \"{}\", expected pixels from 6 to 72",
+            value
+        )),
+    }
+}
+
+/// Heading level of the `[TOC]` depth settings
+pub fn parse_toc_level(value: &str) -> Result<u8, String> {
+    match value.trim().parse::<u8>() {
+        Ok(level) if (1..=6).contains(&level) => Ok(level),
+        _ => Err(format!(
+            "invalid heading level \"{}\", expected 1 to 6",
+            value
+        )),
+    }
+}
+
+/// Options that affect how a document is rendered
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderOptions {
+    pub theme: Theme,
+    /// Whether the table of contents sidebar is shown
+    pub toc: bool,
+    /// Heading levels listed by `[TOC]` and `<!-- toc -->` markers
+    pub toc_depth: TocDepth,
+    pub font: Option<String>,
+    pub font_size: Option<u32>,
+    /// Used by code blocks and inline code
+    pub mono_font: Option<String>,
+    pub mono_font_size: Option<u32>,
+    pub content_width: ContentWidth,
+    pub focus_highlight: FocusHighlight,
+}
+
+impl RenderOptions {
+    /// Parser configured to render with these options
+    pub fn parser(&self) -> MarkdownParser {
+        let mut parser = MarkdownParser::new();
+        self.configure(&mut parser);
+        parser
+    }
+
+    /// Applies these options to an existing parser
+    pub fn configure(&self, parser: &mut MarkdownParser) {
+        parser.set_code_theme(self.theme.code_theme());
+        parser.set_toc_depth(self.toc_depth);
+    }
+
+    /// CSS custom properties of the layout element, the built-in styles are used for unset ones
+    pub fn style(&self) -> String {
+        let mut style = format!(
+            "--crabix-content-width: {};",
+            self.content_width.max_width()
+        );
+        if let Some(font) = &self.font {
+            style += &format!(" --crabix-font: {};", font);
+        }
+        if let Some(size) = self.font_size {
+            style += &format!(" --crabix-font-size: {}px;", size);
+        }
+        if let Some(font) = &self.mono_font {
+            style += &format!(" --crabix-mono-font: {};", font);
+        }
+        if let Some(size) = self.mono_font_size {
+            style += &format!(" --crabix-mono-font-size: {}px;", size);
+        }
+        style
+    }
+
+    /// Changes a display setting by its config key, used by editor plugins
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "theme" => {
+                self.theme =
+                    Theme::from_name(value).ok_or_else(|| format!("unknown theme \"{}\"", value))?
+            }
+            "font" => self.font = parse_font(value)?,
+            "font_size" => self.font_size = parse_font_size(value)?,
+            "mono_font" => self.mono_font = parse_font(value)?,
+            "mono_font_size" => self.mono_font_size = parse_font_size(value)?,
+            "content_width" => {
+                self.content_width = ContentWidth::from_name(value).ok_or_else(|| {
+                    format!(
+                        "unknown content width \"{}\", expected github, wide or full",
+                        value
+                    )
+                })?
+            }
+            "focus_highlight" => {
+                self.focus_highlight = FocusHighlight::from_name(value).ok_or_else(|| {
+                    format!(
+                        "unknown focus highlight \"{}\", expected none, bar or tint",
+                        value
+                    )
+                })?
+            }
+            "toc_min_depth" | "toc_max_depth" => {
+                let level = parse_toc_level(value)?;
+                let mut depth = self.toc_depth;
+                match key {
+                    "toc_min_depth" => depth.min = level,
+                    _ => depth.max = level,
+                }
+                if depth.min > depth.max {
+                    return Err(format!(
+                        "toc_min_depth {} is greater than toc_max_depth {}",
+                        depth.min, depth.max
+                    ));
+                }
+                self.toc_depth = depth;
+            }
+            _ => return Err(format!("unknown setting \"{}\"", key)),
+        }
+        Ok(())
+    }
+
+    /// Document front matter can override `theme`, `toc`, `toc_min_depth` and `toc_max_depth`
+    pub fn with_front_matter(&self, front_matter: Option<&FrontMatter>) -> RenderOptions {
+        let mut options = self.clone();
+        let Some(front_matter) = front_matter else {
+            return options;
+        };
+        if let Some(theme) = &front_matter.theme {
+            match Theme::from_name(theme) {
+                Some(theme) => options.theme = theme,
+                None => log::warn!("Unknown theme in front matter: {}", theme),
+            }
+        }
+        if let Some(toc) = front_matter.toc {
+            options.toc = toc;
+        }
+        let mut depth = options.toc_depth;
+        for (key, level, target) in [
+            ("toc_min_depth", front_matter.toc_min_depth, &mut depth.min),
+            ("toc_max_depth", front_matter.toc_max_depth, &mut depth.max),
+        ] {
+            match level.map(|level| parse_toc_level(&level.to_string())) {
+                Some(Ok(level)) => *target = level,
+                Some(Err(e)) => log::warn!("Front matter {}: {}", key, e),
+                None => {}
+            }
+        }
+        if depth.min <= depth.max {
+            options.toc_depth = depth;
+        } else {
+            log::warn!("Front matter toc_min_depth is greater than toc_max_depth");
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ContentWidth, FocusHighlight, RenderOptions, Theme};
+    use crate::markdown_parser::front_matter::FrontMatter;
+    use crate::markdown_parser::toc_placeholder::TocDepth;
+
+    #[test]
+    fn display_settings() {
+        let mut options = RenderOptions::default();
+        assert_eq!(options.style(), "--crabix-content-width: none;");
+
+        options.set("theme", "dark").unwrap();
+        options.set("font", "\"Inter\", sans-serif").unwrap();
+        options.set("font_size", "18px").unwrap();
+        options.set("mono_font_size", "14").unwrap();
+        options.set("content_width", "GitHub").unwrap();
+        assert_eq!(options.theme, Theme::Dark);
+        assert_eq!(options.content_width, ContentWidth::Github);
+        assert_eq!(
+            options.style(),
+            "--crabix-content-width: 980px; --crabix-font: \"Inter\", sans-serif; --crabix-font-size: 18px; --crabix-mono-font-size: 14px;"
+        );
+
+        options.set("focus_highlight", "bar").unwrap();
+        assert_eq!(options.focus_highlight.class(), "focus-bar");
+        assert_eq!(FocusHighlight::default().class(), "");
+
+        options.set("font", "").unwrap();
+        assert_eq!(options.font, None);
+        assert!(options.set("font", "serif; color: red").is_err());
+        assert!(options.set("font_size", "big").is_err());
+        assert!(options.set("font_size", "2").is_err());
+        assert!(options.set("content_width", "narrow").is_err());
+        assert!(options.set("focus_highlight", "blink").is_err());
+        assert!(options.set("toc", "true").is_err());
+
+        options.set("toc_max_depth", "3").unwrap();
+        assert_eq!(options.toc_depth, TocDepth { min: 1, max: 3 });
+        assert!(options.set("toc_min_depth", "4").is_err());
+        assert!(options.set("toc_max_depth", "7").is_err());
+        assert_eq!(options.toc_depth, TocDepth { min: 1, max: 3 });
+    }
+
+    #[test]
+    fn front_matter_overrides() {
+        let options = RenderOptions {
+            toc_depth: TocDepth { min: 1, max: 3 },
+            ..Default::default()
+        };
+        let front_matter = FrontMatter {
+            theme: Some("dark".to_string()),
+            toc: Some(true),
+            toc_min_depth: Some(2),
+            toc_max_depth: Some(4),
+            ..Default::default()
+        };
+        let overridden = options.with_front_matter(Some(&front_matter));
+        assert_eq!(overridden.theme, Theme::Dark);
+        assert!(overridden.toc);
+        assert_eq!(overridden.toc_depth, TocDepth { min: 2, max: 4 });
+
+        // Only the given bound is overridden, invalid depths are ignored
+        let front_matter = FrontMatter {
+            toc_min_depth: Some(2),
+            ..Default::default()
+        };
+        let overridden = options.with_front_matter(Some(&front_matter));
+        assert_eq!(overridden.toc_depth, TocDepth { min: 2, max: 3 });
+        for (min, max) in [(Some(5), None), (Some(0), None), (None, Some(9))] {
+            let front_matter = FrontMatter {
+                toc_min_depth: min,
+                toc_max_depth: max,
+                ..Default::default()
+            };
+            let overridden = options.with_front_matter(Some(&front_matter));
+            assert_eq!(overridden.toc_depth, TocDepth { min: 1, max: 3 });
+        }
+        assert_eq!(options.with_front_matter(None), options);
+    }
+}
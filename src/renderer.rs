@@ -0,0 +1,198 @@
+//! Parses and renders markdown on a background thread with one long-lived parser.
+//! Only the newest document is rendered, content that is replaced while
+//! waiting or being parsed is dropped.
+use crate::blocks::Block;
+use crate::markdown_parser::front_matter;
+use crate::markdown_parser::heading_anchor::{Heading, HeadingsExt};
+use crate::markdown_parser::wiki_link::WikiPages;
+use crate::markdown_parser::{MarkdownParser, SposIndex, SposesExt};
+use crate::render_options::RenderOptions;
+use crate::title;
+use markdown_it::common::sourcemap::SourceWithLineStarts;
+use markdown_it::parser::core::Root;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderJob {
+    /// Increases with every job, results tell which job they belong to
+    pub generation: u64,
+    pub content: String,
+    /// Options before the document front matter is applied
+    pub options: RenderOptions,
+    pub wiki_pages: Option<WikiPages>,
+}
+
+/// Everything the preview needs from a parsed document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rendered {
+    pub generation: u64,
+    pub blocks: Vec<Block>,
+    pub sposes: SposIndex,
+    pub headings: Vec<Heading>,
+    /// Front matter title, otherwise the first level 1 heading
+    pub title: Option<String>,
+    /// Options with the document front matter applied
+    pub options: RenderOptions,
+}
+
+#[derive(Default)]
+struct Queue {
+    job: Option<RenderJob>,
+    closed: bool,
+}
+
+type SharedQueue = Arc<(Mutex<Queue>, Condvar)>;
+
+/// Handle to the render thread, the thread stops when the handle is dropped
+pub struct Renderer {
+    queue: SharedQueue,
+}
+
+impl Renderer {
+    pub fn new() -> (Renderer, UnboundedReceiver<Rendered>) {
+        let queue = SharedQueue::default();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let worker = queue.clone();
+        thread::Builder::new()
+            .name("renderer".to_string())
+            .spawn(move || run(worker, tx))
+            .expect("Can't start the render thread");
+        (Renderer { queue }, rx)
+    }
+
+    /// Replaces the job waiting to be rendered, if any
+    pub fn submit(&self, job: RenderJob) {
+        let (queue, ready) = &*self.queue;
+        queue.lock().unwrap().job = Some(job);
+        ready.notify_one();
+    }
+}
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        let (queue, ready) = &*self.queue;
+        queue.lock().unwrap().closed = true;
+        ready.notify_one();
+    }
+}
+
+fn next_job(queue: &SharedQueue) -> Option<RenderJob> {
+    let (queue, ready) = &**queue;
+    let mut queue = queue.lock().unwrap();
+    loop {
+        if queue.closed {
+            return None;
+        }
+        if let Some(job) = queue.job.take() {
+            return Some(job);
+        }
+        queue = ready.wait(queue).unwrap();
+    }
+}
+
+fn is_stale(queue: &SharedQueue) -> bool {
+    let queue = queue.0.lock().unwrap();
+    queue.closed || queue.job.is_some()
+}
+
+fn run(queue: SharedQueue, results: UnboundedSender<Rendered>) {
+    let mut parser = MarkdownParser::new();
+    // The preview scrolls to and marks the word at the editor cursor
+    parser.set_inline_positions(true);
+    let mut configured: Option<(RenderOptions, Option<WikiPages>)> = None;
+    while let Some(job) = next_job(&queue) {
+        let front_matter = front_matter::extract(&job.content);
+        let options = job.options.with_front_matter(front_matter.as_ref());
+        let configuration = (options.clone(), job.wiki_pages);
+        if configured.as_ref() != Some(&configuration) {
+            options.configure(&mut parser);
+            parser.set_wiki_pages(configuration.1.clone().unwrap_or_default());
+            configured = Some(configuration);
+        }
+
+        log::trace!("Parsing markdown, generation {}", job.generation);
+        let ast = parser.parse(&job.content);
+        // Rendering is the expensive part for highlighted code, skip it for replaced content
+        if is_stale(&queue) {
+            log::trace!("Generation {} is stale", job.generation);
+            continue;
+        }
+        let root = ast.cast::<Root>().unwrap();
+        let sposes = root.ext.get::<SposesExt>().unwrap().sposes.clone();
+        let headings = root.ext.get::<HeadingsExt>().unwrap().headings.clone();
+        let mapping = SourceWithLineStarts::new(&job.content);
+        let blocks = ast
+            .children
+            .iter()
+            .map(|node| {
+                let start_line = node
+                    .srcmap
+                    .map_or(0, |map| map.get_positions(&mapping).0 .0);
+                Block::new(start_line, node.render())
+            })
+            .collect();
+        let rendered = Rendered {
+            generation: job.generation,
+            blocks,
+            title: title::document_title(front_matter.and_then(|fm| fm.title), &headings),
+            sposes,
+            headings,
+            options,
+        };
+        log::trace!("Markdown rendered, generation {}", job.generation);
+        if !is_stale(&queue) && results.send(rendered).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RenderJob, Renderer};
+    use crate::render_options::{RenderOptions, Theme};
+
+    #[test]
+    fn newest_content_is_rendered() {
+        let (renderer, mut results) = Renderer::new();
+        let job = |generation: u64| RenderJob {
+            generation,
+            content: format!("# Version {}\n\ntext\n", generation),
+            options: RenderOptions::default(),
+            wiki_pages: None,
+        };
+        for generation in 1..=20 {
+            renderer.submit(job(generation));
+        }
+        let mut last = 0;
+        let rendered = loop {
+            let rendered = results.blocking_recv().unwrap();
+            assert!(rendered.generation > last, "results arrive in order");
+            last = rendered.generation;
+            if rendered.generation == 20 {
+                break rendered;
+            }
+        };
+        assert_eq!(rendered.blocks.len(), 2);
+        assert!(rendered.blocks[0].html.contains("Version 20"));
+        assert_eq!(rendered.blocks[1].id.start_line, 3);
+        // The preview gets column positions, exported documents don't
+        assert!(rendered.blocks[1].html.contains("data-spos=\"3:1-3:4\""));
+        assert_eq!(rendered.title, Some("Version 20".to_string()));
+        assert_eq!(rendered.headings.len(), 1);
+        assert_eq!(rendered.sposes.innermost(3).map(|s| s.start_line), Some(3));
+
+        // Front matter options are applied to the result
+        renderer.submit(RenderJob {
+            content: "---\ntheme: dark\n---\ntext\n".to_string(),
+            ..job(21)
+        });
+        let rendered = results.blocking_recv().unwrap();
+        assert_eq!(rendered.generation, 21);
+        assert_eq!(rendered.options.theme, Theme::Dark);
+
+        drop(renderer);
+        assert!(results.blocking_recv().is_none());
+    }
+}
@@ -0,0 +1,100 @@
+//! How live scrolling brings the block at the editor cursor into view
+
+/// Where in the window the cursor line ends up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollAnchor {
+    Top,
+    Center,
+    /// Percent of the window height from the top
+    Percent(u8),
+}
+
+impl ScrollAnchor {
+    /// `top`, `center` or a percentage like `30%`
+    pub fn parse(anchor: &str) -> Result<ScrollAnchor, String> {
+        match anchor.trim().to_lowercase().as_str() {
+            "top" => Ok(ScrollAnchor::Top),
+            "center" => Ok(ScrollAnchor::Center),
+            percent => percent
+                .strip_suffix('%')
+                .and_then(|p| p.trim().parse::<u8>().ok())
+                .filter(|p| *p <= 100)
+                .map(ScrollAnchor::Percent)
+                .ok_or_else(|| {
+                    format!(
+                        "invalid scroll anchor \"{}\", expected top, center or a percentage like 30%",
+                        anchor
+                    )
+                }),
+        }
+    }
+
+    /// Distance from the top of the window as a fraction of its height
+    pub fn fraction(&self) -> f64 {
+        match self {
+            ScrollAnchor::Top => 0.0,
+            ScrollAnchor::Center => 0.5,
+            ScrollAnchor::Percent(percent) => f64::from(*percent) / 100.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollSync {
+    pub anchor: ScrollAnchor,
+    /// Animate scrolling instead of jumping
+    pub smooth: bool,
+    /// Scroll within blocks taller than a line, by the cursor line position in the block
+    pub interpolate: bool,
+}
+
+impl Default for ScrollSync {
+    fn default() -> Self {
+        ScrollSync {
+            anchor: ScrollAnchor::Center,
+            smooth: true,
+            interpolate: true,
+        }
+    }
+}
+
+impl ScrollSync {
+    /// Where the cursor line is in a block, as a fraction of the block height
+    pub fn block_fraction(&self, start_line: u32, end_line: u32, line: u32) -> f64 {
+        let steps = end_line.saturating_sub(start_line);
+        if !self.interpolate || steps == 0 {
+            return 0.0;
+        }
+        let line = line.clamp(start_line, end_line);
+        f64::from(line - start_line) / f64::from(steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScrollAnchor, ScrollSync};
+
+    #[test]
+    fn scroll_anchors() {
+        assert_eq!(ScrollAnchor::parse("top"), Ok(ScrollAnchor::Top));
+        assert_eq!(ScrollAnchor::parse("Center"), Ok(ScrollAnchor::Center));
+        assert_eq!(ScrollAnchor::parse("30%"), Ok(ScrollAnchor::Percent(30)));
+        assert_eq!(ScrollAnchor::parse("30%").unwrap().fraction(), 0.3);
+        for invalid in ["", "middle", "30", "130%", "-5%"] {
+            assert!(ScrollAnchor::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn block_fractions() {
+        let sync = ScrollSync::default();
+        assert_eq!(sync.block_fraction(10, 20, 15), 0.5);
+        assert_eq!(sync.block_fraction(10, 10, 10), 0.0);
+        assert_eq!(sync.block_fraction(10, 20, 30), 1.0);
+        let sync = ScrollSync {
+            interpolate: false,
+            ..Default::default()
+        };
+        assert_eq!(sync.block_fraction(10, 20, 15), 0.0);
+    }
+}
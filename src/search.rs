@@ -0,0 +1,279 @@
+use crate::workspace::Workspace;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+
+// Characters of context shown before the match in a snippet
+const SNIPPET_CONTEXT: usize = 40;
+const SNIPPET_LENGTH: usize = 120;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Document {
+    path: PathBuf,
+    name: String,
+    lines: Vec<String>,
+    // Lowercased lines, queries are case insensitive
+    folded: Vec<String>,
+}
+
+/// Markdown text of all workspace files
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchIndex {
+    documents: Vec<Document>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub path: PathBuf,
+    /// Path relative to the workspace root
+    pub name: String,
+    /// 1-based source line of the match
+    pub line: u32,
+    pub snippet: String,
+    /// Byte range of the first match in the snippet
+    pub highlight: Range<usize>,
+    pub score: f64,
+}
+
+// Byte range of the first case insensitive occurrence of the lowercased needle
+fn find_folded(haystack: &str, needle: &str) -> Option<Range<usize>> {
+    for (start, _) in haystack.char_indices() {
+        let mut needle_chars = needle.chars().peekable();
+        for (i, c) in haystack[start..].char_indices() {
+            let mut matched = true;
+            for lower in c.to_lowercase() {
+                if needle_chars.next() != Some(lower) {
+                    matched = false;
+                    break;
+                }
+            }
+            if !matched {
+                break;
+            }
+            if needle_chars.peek().is_none() {
+                return Some(start..start + i + c.len_utf8());
+            }
+        }
+    }
+    None
+}
+
+fn snippet(line: &str, matched: Range<usize>) -> (String, Range<usize>) {
+    let line_start = line.len() - line.trim_start().len();
+    let mut start = matched
+        .start
+        .saturating_sub(SNIPPET_CONTEXT)
+        .max(line_start);
+    while !line.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (start + SNIPPET_LENGTH).max(matched.end).min(line.len());
+    while !line.is_char_boundary(end) {
+        end += 1;
+    }
+    let prefix = if start > line_start { "…" } else { "" };
+    let suffix = if end < line.trim_end().len() {
+        "…"
+    } else {
+        ""
+    };
+    let snippet = format!("{}{}{}", prefix, line[start..end].trim_end(), suffix);
+    let offset = prefix.len() + matched.start - start;
+    (snippet, offset..offset + matched.len())
+}
+
+impl SearchIndex {
+    pub fn build(workspace: &Workspace) -> SearchIndex {
+        let documents = workspace.files.iter().filter_map(|path| {
+            let content = match fs::read_to_string(path) {
+                Ok(content) => content,
+                Err(e) => {
+                    log::warn!("Failed to index {}: {}", path.display(), e);
+                    return None;
+                }
+            };
+            let name = path
+                .strip_prefix(&workspace.root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string();
+            Some((path.clone(), name, content))
+        });
+        SearchIndex::from_documents(documents)
+    }
+
+    pub fn from_documents(documents: impl IntoIterator<Item = (PathBuf, String, String)>) -> Self {
+        SearchIndex {
+            documents: documents
+                .into_iter()
+                .map(|(path, name, content)| {
+                    let lines: Vec<String> = content.lines().map(Into::into).collect();
+                    Document {
+                        path,
+                        name,
+                        folded: lines.iter().map(|l| l.to_lowercase()).collect(),
+                        lines,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Lines that contain any of the query words. Rare words, lines with more
+    /// of the words, headings and files that mention the words often rank higher.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let mut terms: Vec<String> = vec![];
+        for term in query.split_whitespace().map(str::to_lowercase) {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+        if terms.is_empty() {
+            return vec![];
+        }
+
+        let documents = self.documents.len() as f64;
+        let idf: Vec<f64> = terms
+            .iter()
+            .map(|term| {
+                let frequency = self
+                    .documents
+                    .iter()
+                    .filter(|d| d.folded.iter().any(|line| line.contains(term.as_str())))
+                    .count();
+                (1.0 + documents / frequency.max(1) as f64).ln()
+            })
+            .collect();
+
+        let mut hits = vec![];
+        for document in self.documents.iter() {
+            let term_counts: Vec<usize> = terms
+                .iter()
+                .map(|term| {
+                    document
+                        .folded
+                        .iter()
+                        .map(|line| line.matches(term.as_str()).count())
+                        .sum()
+                })
+                .collect();
+            let document_score: f64 = term_counts
+                .iter()
+                .zip(idf.iter())
+                .map(|(&count, idf)| (1.0 + count as f64).ln() * idf)
+                .sum();
+            if document_score == 0.0 {
+                continue;
+            }
+            let folded_name = document.name.to_lowercase();
+
+            for (number, folded) in document.folded.iter().enumerate() {
+                let matched: Vec<usize> = (0..terms.len())
+                    .filter(|&i| folded.contains(terms[i].as_str()))
+                    .collect();
+                if matched.is_empty() {
+                    continue;
+                }
+                let mut score: f64 = matched.iter().map(|&i| idf[i]).sum::<f64>() * 2.0;
+                if folded.trim_start().starts_with('#') {
+                    score *= 1.5;
+                }
+                if terms.iter().any(|term| folded_name.contains(term.as_str())) {
+                    score += 1.0;
+                }
+                score += document_score * 0.1;
+
+                let line = &document.lines[number];
+                let first_term = &terms[matched[0]];
+                let Some(range) = find_folded(line, first_term) else {
+                    continue;
+                };
+                let (snippet, highlight) = snippet(line, range);
+                hits.push(SearchHit {
+                    path: document.path.clone(),
+                    name: document.name.clone(),
+                    line: number as u32 + 1,
+                    snippet,
+                    highlight,
+                    score,
+                });
+            }
+        }
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_folded, snippet, SearchIndex};
+    use std::path::PathBuf;
+
+    fn index() -> SearchIndex {
+        let document = |name: &str, content: &str| {
+            (
+                PathBuf::from("/docs").join(name),
+                name.to_string(),
+                content.to_string(),
+            )
+        };
+        SearchIndex::from_documents([
+            document(
+                "install.md",
+                "# Install\n\nRun cargo install crabix-desktop\n",
+            ),
+            document(
+                "usage.md",
+                "# Usage\n\nOpen a file\n\n## Socket\nSend the file through the socket\n",
+            ),
+            document("socket.md", "Protocol of the socket connection\n"),
+        ])
+    }
+
+    #[test]
+    fn ranked_hits() {
+        let index = index();
+        let hits: Vec<(String, u32)> = index
+            .search("socket", 10)
+            .iter()
+            .map(|hit| (hit.name.clone(), hit.line))
+            .collect();
+        assert_eq!(
+            hits,
+            vec![
+                ("socket.md".to_string(), 1),
+                ("usage.md".to_string(), 5),
+                ("usage.md".to_string(), 6)
+            ]
+        );
+
+        let hits = index.search("INSTALL cargo", 10);
+        assert_eq!(hits[0].line, 3);
+        assert_eq!(hits[0].snippet, "Run cargo install crabix-desktop");
+        assert_eq!(&hits[0].snippet[hits[0].highlight.clone()], "install");
+        assert_eq!(hits.len(), 2);
+
+        assert!(index.search("missing", 10).is_empty());
+        assert!(index.search("  ", 10).is_empty());
+        assert_eq!(index.search("the", 1).len(), 1);
+    }
+
+    #[test]
+    fn snippets() {
+        assert_eq!(find_folded("Привіт Світ", "світ"), Some(13..21));
+        assert_eq!(find_folded("abc", "abcd"), None);
+
+        let line = format!("  {} needle {}", "a".repeat(60), "b".repeat(200));
+        let range = find_folded(&line, "needle").unwrap();
+        let (snippet, highlight) = snippet(&line, range);
+        assert!(snippet.starts_with("…aaa"));
+        assert!(snippet.ends_with("bbb…"));
+        assert_eq!(&snippet[highlight], "needle");
+    }
+}
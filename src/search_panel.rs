@@ -0,0 +1,110 @@
+use crate::search::{SearchHit, SearchIndex};
+use crate::{MARKDOWN_CONTENT, WORKSPACE};
+use dioxus::prelude::*;
+use fermi::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+// Hits shown for a query
+const SEARCH_RESULTS_LIMIT: usize = 50;
+// Changes in quick succession, e.g. typing in the editor, are indexed once
+const REINDEX_DELAY: Duration = Duration::from_millis(300);
+
+#[inline_props]
+pub fn SearchPanel<'a>(cx: Scope<'a>, on_open: EventHandler<'a, (String, u32)>) -> Element<'a> {
+    let visible = use_state(cx, || false);
+    let query = use_state(cx, String::new);
+    // Generation of the index, it grows with every rebuild
+    let index = use_ref(cx, || (0, SearchIndex::default()));
+    let workspace = use_read(cx, WORKSPACE);
+    let content = use_read(cx, MARKDOWN_CONTENT);
+
+    // Reindexed in the background while the panel is open, when files are added
+    // or deleted and when the document changes
+    use_future(
+        cx,
+        (visible.get(), workspace, content),
+        |(visible, workspace, _)| {
+            let index = index.clone();
+            async move {
+                let Some(workspace) = workspace.filter(|_| visible) else {
+                    return;
+                };
+                tokio::time::sleep(REINDEX_DELAY).await;
+                match tokio::task::spawn_blocking(move || SearchIndex::build(&workspace)).await {
+                    Ok(built) => {
+                        let mut index = index.write();
+                        *index = (index.0 + 1, built);
+                    }
+                    Err(e) => log::error!("Search indexing failed: {}", e),
+                }
+            }
+        },
+    );
+    // Live updates render the panel too, only a new query or index is searched again
+    let searched =
+        cx.use_hook(|| RefCell::new((None::<(String, u64)>, Rc::<[SearchHit]>::from([]))));
+    let Some(workspace) = workspace else {
+        return None;
+    };
+
+    let toggle = move |_| visible.set(!*visible.get());
+    let key = (query.get().clone(), index.read().0);
+    if searched.borrow().0.as_ref() != Some(&key) {
+        let hits = index.read().1.search(query.get(), SEARCH_RESULTS_LIMIT);
+        *searched.borrow_mut() = (Some(key), hits.into());
+    }
+    let hits = searched.borrow().1.clone();
+    let hit_count = hits.len();
+
+    cx.render(rsx! {
+        button {
+            class: "search-toggle",
+            title: "Search in workspace",
+            onclick: toggle,
+            "🔍"
+        }
+        if *visible.get() {
+            rsx! {
+                aside {
+                    class: "search-panel",
+                    input {
+                        class: "search-input",
+                        r#type: "search",
+                        placeholder: "Search in {workspace.files.len()} files",
+                        autofocus: true,
+                        value: "{query}",
+                        oninput: move |evt| query.set(evt.value.clone())
+                    }
+                    if !query.get().trim().is_empty() {
+                        rsx! { div { class: "search-summary", "{hit_count} results" } }
+                    }
+                    ul {
+                        hits.iter().map(|hit| {
+                            let path = hit.path.display().to_string();
+                            let line = hit.line;
+                            let before = &hit.snippet[..hit.highlight.start];
+                            let matched = &hit.snippet[hit.highlight.clone()];
+                            let after = &hit.snippet[hit.highlight.end..];
+                            rsx! {
+                                li {
+                                    key: "{path}:{line}",
+                                    class: "search-hit",
+                                    onclick: move |_| on_open.call((path.clone(), line)),
+                                    div { class: "search-hit-location", "{hit.name}:{line}" }
+                                    div {
+                                        class: "search-hit-snippet",
+                                        "{before}"
+                                        mark { "{matched}" }
+                                        "{after}"
+                                    }
+                                }
+                            }
+                        })
+                    }
+                }
+            }
+        }
+    })
+}
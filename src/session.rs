@@ -0,0 +1,225 @@
+//! Window geometry, zoom and scroll positions restored on the next launch
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Window size and position in logical pixels, `WIDTHxHEIGHT[+X+Y]` like X11 `-geometry`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Geometry {
+    pub width: u32,
+    pub height: u32,
+    pub position: Option<(i32, i32)>,
+}
+
+impl Geometry {
+    pub fn parse(geometry: &str) -> Result<Geometry, String> {
+        let invalid = || {
+            format!(
+                "invalid geometry \"{}\", expected WIDTHxHEIGHT or WIDTHxHEIGHT+X+Y",
+                geometry
+            )
+        };
+        let split = geometry.find(['+', '-']).unwrap_or(geometry.len());
+        let (size, position) = geometry.split_at(split);
+        let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+        let width: u32 = width.parse().map_err(|_| invalid())?;
+        let height: u32 = height.parse().map_err(|_| invalid())?;
+        if width == 0 || height == 0 {
+            return Err(invalid());
+        }
+
+        let position = if position.is_empty() {
+            None
+        } else {
+            // Signs are part of the numbers, `+-10+20` is also accepted
+            let second = position[1..]
+                .find(['+', '-'])
+                .map(|i| i + 1)
+                .ok_or_else(invalid)?;
+            let coordinate = |value: &str| {
+                value
+                    .strip_prefix('+')
+                    .unwrap_or(value)
+                    .parse::<i32>()
+                    .map_err(|_| invalid())
+            };
+            Some((
+                coordinate(&position[..second])?,
+                coordinate(&position[second..])?,
+            ))
+        };
+        Ok(Geometry {
+            width,
+            height,
+            position,
+        })
+    }
+}
+
+impl fmt::Display for Geometry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)?;
+        if let Some((x, y)) = self.position {
+            write!(f, "{:+}{:+}", x, y)?;
+        }
+        Ok(())
+    }
+}
+
+fn default_zoom() -> f64 {
+    1.0
+}
+
+/// `session.json` in the state directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub window: Option<Geometry>,
+    #[serde(default = "default_zoom")]
+    pub zoom: f64,
+    /// Vertical scroll offset in pixels by canonical file path
+    pub scroll: BTreeMap<String, f64>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session {
+            window: None,
+            zoom: default_zoom(),
+            scroll: BTreeMap::new(),
+        }
+    }
+}
+
+/// Updated in place by the preview, which doesn't need to re-render for it
+pub type SharedSession = Rc<RefCell<Session>>;
+
+pub fn session_path() -> Option<PathBuf> {
+    Some(crate::config::state_dir()?.join("session.json"))
+}
+
+// The same file opened by different paths shares its scroll offset
+fn scroll_key(file: &str) -> Option<String> {
+    let path = Path::new(file).canonicalize().ok()?;
+    Some(path.to_str()?.to_string())
+}
+
+impl Session {
+    /// Missing or broken session file is a new session
+    pub fn load(path: &Path) -> Session {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Session::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            log::warn!("Ignoring {}: {}", path.display(), e);
+            Session::default()
+        })
+    }
+
+    /// Where the file was left, relative paths are resolved against the working directory
+    pub fn scroll_offset(&self, file: &str) -> Option<f64> {
+        self.scroll.get(&scroll_key(file)?).copied()
+    }
+
+    pub fn set_scroll_offset(&mut self, file: &str, offset: f64) {
+        match scroll_key(file) {
+            Some(key) => {
+                self.scroll.insert(key, offset);
+            }
+            None => log::debug!("Scroll offset of {} isn't saved", file),
+        }
+    }
+
+    /// Scroll positions of deleted files are dropped
+    pub fn save(&mut self, path: &Path) {
+        // Relative paths of older sessions can't be resolved anymore
+        self.scroll
+            .retain(|file, _| Path::new(file).is_absolute() && Path::new(file).exists());
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, serde_json::to_string_pretty(self).unwrap()));
+        match result {
+            Ok(()) => log::debug!("Session saved to {}", path.display()),
+            Err(e) => log::warn!("Failed to save {}: {}", path.display(), e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Geometry, Session};
+    use crate::test_util::TempDir;
+    use std::fs;
+
+    #[test]
+    fn geometry_notation() {
+        let geometry = |width, height, position| Geometry {
+            width,
+            height,
+            position,
+        };
+        assert_eq!(Geometry::parse("800x600"), Ok(geometry(800, 600, None)));
+        assert_eq!(
+            Geometry::parse("1200x900+10+20"),
+            Ok(geometry(1200, 900, Some((10, 20))))
+        );
+        assert_eq!(
+            Geometry::parse("640x480-5+-30"),
+            Ok(geometry(640, 480, Some((-5, -30))))
+        );
+        for invalid in [
+            "",
+            "800",
+            "800x",
+            "0x600",
+            "800x600+10",
+            "800x600+a+b",
+            "axb",
+        ] {
+            assert!(Geometry::parse(invalid).is_err(), "{}", invalid);
+        }
+        for notation in ["800x600", "1200x900+10+20", "640x480-5-30"] {
+            assert_eq!(Geometry::parse(notation).unwrap().to_string(), notation);
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = TempDir::new("session");
+        let path = dir.path().join("state").join("session.json");
+        assert_eq!(Session::load(&path), Session::default());
+
+        let existing = dir.path().join("notes.md");
+        fs::write(&existing, "# Notes").unwrap();
+        let mut session = Session {
+            window: Some(Geometry::parse("800x600+1+2").unwrap()),
+            zoom: 1.21,
+            ..Default::default()
+        };
+        session.set_scroll_offset(&existing.display().to_string(), 420.0);
+        session.scroll.insert("/deleted/notes.md".into(), 10.0);
+        session.scroll.insert("notes.md".into(), 20.0);
+        // Other paths of the same file
+        let other = dir.path().join(".").join("notes.md");
+        assert_eq!(
+            session.scroll_offset(&other.display().to_string()),
+            Some(420.0)
+        );
+        session.set_scroll_offset("/deleted/todo.md", 30.0);
+        session.save(&path);
+
+        let loaded = Session::load(&path);
+        assert_eq!(loaded, session);
+        assert_eq!(loaded.scroll.len(), 1);
+
+        fs::write(&path, "{\"zoom\": 1.5}").unwrap();
+        assert_eq!(Session::load(&path).zoom, 1.5);
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(Session::load(&path), Session::default());
+    }
+}
@@ -0,0 +1,60 @@
+//! Window title naming the document and where its content comes from
+use crate::markdown_parser::heading_anchor::Heading;
+
+/// Front matter `title`, otherwise the first level 1 heading
+pub fn document_title(front_matter_title: Option<String>, headings: &[Heading]) -> Option<String> {
+    front_matter_title
+        .filter(|t| !t.trim().is_empty())
+        .or_else(|| {
+            headings
+                .iter()
+                .find(|h| h.level == 1 && !h.title.is_empty())
+                .map(|h| h.title.clone())
+        })
+}
+
+/// `TITLE — SOURCE`, e.g. `Setup — install.md` or `Notes — buffer 3`.
+/// `default` stands in for a document without a title.
+pub fn window_title(document_title: Option<&str>, source: Option<&str>, default: &str) -> String {
+    let title = document_title.unwrap_or(default);
+    match source {
+        Some(source) => format!("{} — {}", title, source),
+        None => title.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{document_title, window_title};
+    use crate::markdown_parser::heading_anchor::HeadingsExt;
+    use crate::markdown_parser::MarkdownParser;
+    use markdown_it::parser::core::Root;
+
+    #[test]
+    fn titles() {
+        let ast = MarkdownParser::new().parse("## Intro\n\n# Setup\n\n# Usage\n");
+        let headings = &ast
+            .cast::<Root>()
+            .unwrap()
+            .ext
+            .get::<HeadingsExt>()
+            .unwrap()
+            .headings;
+        assert_eq!(document_title(None, headings), Some("Setup".to_string()));
+        assert_eq!(
+            document_title(Some("Guide".to_string()), headings),
+            Some("Guide".to_string())
+        );
+        assert_eq!(document_title(Some(" ".to_string()), &[]), None);
+
+        assert_eq!(
+            window_title(Some("Setup"), Some("install.md"), "Crabix Desktop"),
+            "Setup — install.md"
+        );
+        assert_eq!(
+            window_title(None, Some("socket"), "Crabix Desktop"),
+            "Crabix Desktop — socket"
+        );
+        assert_eq!(window_title(None, None, "Notes"), "Notes");
+    }
+}
@@ -0,0 +1,40 @@
+use crate::markdown_parser::heading_anchor::Heading;
+use crate::TOC_VISIBLE;
+use dioxus::prelude::*;
+use fermi::*;
+
+#[inline_props]
+pub fn TocSidebar(cx: Scope, headings: Vec<Heading>, source_line: u32) -> Element<'a> {
+    let visible = *use_read(cx, TOC_VISIBLE);
+    let setVisible = use_set(cx, TOC_VISIBLE);
+    let current = Heading::section_at(*source_line, headings).map(|h| &h.slug);
+
+    cx.render(rsx! {
+        button {
+            class: "toc-toggle",
+            title: "Table of contents",
+            onclick: move |_| setVisible(!visible),
+            "☰"
+        }
+        if visible {
+            rsx! {
+                nav {
+                    class: "toc-sidebar",
+                    ul {
+                        headings.iter().map(|h| {
+                            let state = if current == Some(&h.slug) { "current" } else { "" };
+                            rsx! {
+                                li {
+                                    key: "{h.slug}",
+                                    class: "toc-entry toc-level-{h.level} {state}",
+                                    "data-slug": "{h.slug}",
+                                    a { href: "#{h.slug}", "{h.title}" }
+                                }
+                            }
+                        })
+                    }
+                }
+            }
+        }
+    })
+}
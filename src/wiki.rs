@@ -0,0 +1,106 @@
+use crate::markdown_parser::wiki_link::{link_parser, link_targets, WikiPages};
+use crate::workspace::markdown_files;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// Markdown file in the wiki with the pages it links to
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiPage {
+    pub path: PathBuf,
+    /// Path relative to the wiki root without extension
+    pub name: String,
+    pub links: Vec<String>,
+}
+
+/// Index of all markdown files under the wiki root
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WikiIndex {
+    pub root: PathBuf,
+    pub pages: WikiPages,
+    files: Vec<WikiPage>,
+}
+
+impl WikiIndex {
+    pub fn build(root: &Path) -> io::Result<WikiIndex> {
+        let root = root.canonicalize()?;
+        // Shallow pages win when several have the same name
+        let paths = markdown_files(&root)?;
+        let md = link_parser();
+
+        let mut index = WikiIndex {
+            root: root.clone(),
+            ..Default::default()
+        };
+        for path in paths {
+            let relative = path.strip_prefix(&root).unwrap().to_path_buf();
+            let links = match fs::read_to_string(&path) {
+                Ok(content) => link_targets(&md, &content),
+                Err(e) => {
+                    log::warn!("Failed to index {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            index.pages.insert(&relative, path.clone());
+            index.files.push(WikiPage {
+                name: relative.with_extension("").to_string_lossy().into(),
+                path,
+                links,
+            });
+        }
+        Ok(index)
+    }
+
+    /// Other pages that link to the given canonical path, sorted by name
+    pub fn backlinks(&self, path: &Path) -> Vec<&WikiPage> {
+        let mut backlinks: Vec<&WikiPage> = self
+            .files
+            .iter()
+            .filter(|page| page.path != path)
+            .filter(|page| {
+                page.links
+                    .iter()
+                    .any(|link| self.pages.resolve(link).map(PathBuf::as_path) == Some(path))
+            })
+            .collect();
+        backlinks.sort_by(|a, b| a.name.cmp(&b.name));
+        backlinks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WikiIndex;
+    use crate::test_util::TempDir;
+    use std::fs;
+
+    #[test]
+    fn backlinks() {
+        let dir = TempDir::new("wiki");
+        let root = dir.path();
+        fs::create_dir_all(root.join("notes")).unwrap();
+        fs::create_dir_all(root.join(".obsidian")).unwrap();
+        fs::write(root.join("Home.md"), "[[Todo]] and [[notes/Ideas|ideas]]").unwrap();
+        fs::write(
+            root.join("notes/Ideas.md"),
+            "Back to [[home#intro]], [[Ideas]]",
+        )
+        .unwrap();
+        fs::write(root.join("notes/Todo.md"), "`[[Home]]` isn't a link").unwrap();
+        fs::write(root.join(".obsidian/Cache.md"), "[[Home]]").unwrap();
+        fs::write(root.join("notes/image.png"), "[[Home]]").unwrap();
+
+        let index = WikiIndex::build(root).unwrap();
+        let root = root.canonicalize().unwrap();
+        let names = |path: &str| -> Vec<String> {
+            index
+                .backlinks(&root.join(path))
+                .iter()
+                .map(|page| page.name.clone())
+                .collect()
+        };
+        assert_eq!(names("Home.md"), vec!["notes/Ideas"]);
+        assert_eq!(names("notes/Ideas.md"), vec!["Home"]);
+        assert_eq!(names("notes/Todo.md"), vec!["Home"]);
+        assert_eq!(names("Missing.md"), Vec::<String>::new());
+    }
+}
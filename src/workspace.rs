@@ -0,0 +1,306 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory opened with `crabix-desktop docs/` or several files
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    pub root: PathBuf,
+    /// Absolute paths of all markdown files, shallow files first
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileTreeNode {
+    Dir {
+        name: String,
+        path: PathBuf,
+        children: Vec<FileTreeNode>,
+    },
+    File {
+        name: String,
+        path: PathBuf,
+    },
+}
+
+impl FileTreeNode {
+    fn name(&self) -> &str {
+        match self {
+            FileTreeNode::Dir { name, .. } | FileTreeNode::File { name, .. } => name,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            FileTreeNode::Dir { path, .. } | FileTreeNode::File { path, .. } => path,
+        }
+    }
+}
+
+fn is_markdown(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("md" | "markdown")
+    )
+}
+
+/// Markdown files under the root, shallow files first and then by path.
+/// Symlinked directories aren't followed, they can point at an ancestor.
+/// Unreadable subdirectories and entries are skipped.
+pub fn markdown_files(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    let mut dirs = vec![];
+    for entry in fs::read_dir(root)? {
+        add_entry(entry, &mut dirs, &mut paths);
+    }
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("Skipped {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+        for entry in entries {
+            add_entry(entry, &mut dirs, &mut paths);
+        }
+    }
+    paths.sort_by_key(|p| (p.components().count(), p.clone()));
+    Ok(paths)
+}
+
+fn add_entry(entry: io::Result<fs::DirEntry>, dirs: &mut Vec<PathBuf>, paths: &mut Vec<PathBuf>) {
+    let entry = match entry {
+        Ok(entry) => entry,
+        Err(e) => {
+            log::warn!("Skipped a directory entry: {}", e);
+            return;
+        }
+    };
+    let path = entry.path();
+    // Skips .git, .obsidian and other tool directories
+    if path
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+    {
+        return;
+    }
+    let file_type = match entry.file_type() {
+        Ok(file_type) => file_type,
+        Err(e) => {
+            log::warn!("Skipped {}: {}", path.display(), e);
+            return;
+        }
+    };
+    if file_type.is_dir() {
+        dirs.push(path);
+    } else if is_markdown(&path) && (file_type.is_file() || path.is_file()) {
+        paths.push(path);
+    }
+}
+
+impl Workspace {
+    pub fn scan(root: &Path) -> io::Result<Workspace> {
+        let root = root.canonicalize()?;
+        Ok(Workspace {
+            files: markdown_files(&root)?,
+            root,
+        })
+    }
+
+    /// Files given on the command line, the root is their closest common directory.
+    /// Directories can only be opened on their own with `scan`.
+    pub fn from_files(paths: &[PathBuf]) -> io::Result<Workspace> {
+        let files = paths
+            .iter()
+            .map(|path| path.canonicalize())
+            .collect::<io::Result<Vec<PathBuf>>>()?;
+        if let Some(dir) = files.iter().find(|file| file.is_dir()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is a directory, it can't be opened together with other files",
+                    dir.display()
+                ),
+            ));
+        }
+        let mut root = match files.first().and_then(|file| file.parent()) {
+            Some(dir) => dir.to_path_buf(),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "no files")),
+        };
+        while !files.iter().all(|file| file.starts_with(&root)) {
+            root.pop();
+        }
+        Ok(Workspace { root, files })
+    }
+
+    /// The document shown when the workspace is opened: README, index or the first file
+    pub fn default_file(&self) -> Option<&PathBuf> {
+        self.files
+            .iter()
+            .find(|path| {
+                path.parent() == Some(&self.root)
+                    && path.file_stem().is_some_and(|stem| {
+                        let stem = stem.to_string_lossy().to_lowercase();
+                        stem == "readme" || stem == "index"
+                    })
+            })
+            .or_else(|| self.files.first())
+    }
+
+    /// Directories first, then files, both sorted by name
+    pub fn tree(&self) -> Vec<FileTreeNode> {
+        fn insert(nodes: &mut Vec<FileTreeNode>, dir: &Path, components: &[String], path: &Path) {
+            let [name, rest @ ..] = components else {
+                return;
+            };
+            if rest.is_empty() {
+                nodes.push(FileTreeNode::File {
+                    name: name.clone(),
+                    path: path.to_path_buf(),
+                });
+                return;
+            }
+            let position = nodes
+                .iter()
+                .position(|node| matches!(node, FileTreeNode::Dir { name: n, .. } if n == name));
+            let position = position.unwrap_or_else(|| {
+                nodes.push(FileTreeNode::Dir {
+                    name: name.clone(),
+                    path: dir.join(name),
+                    children: vec![],
+                });
+                nodes.len() - 1
+            });
+            if let FileTreeNode::Dir {
+                path: dir,
+                children,
+                ..
+            } = &mut nodes[position]
+            {
+                insert(children, dir, rest, path);
+            }
+        }
+
+        fn sort(nodes: &mut [FileTreeNode]) {
+            nodes.sort_by_key(|node| {
+                (
+                    matches!(node, FileTreeNode::File { .. }),
+                    node.name().to_lowercase(),
+                )
+            });
+            for node in nodes.iter_mut() {
+                if let FileTreeNode::Dir { children, .. } = node {
+                    sort(children);
+                }
+            }
+        }
+
+        let mut nodes = vec![];
+        for path in self.files.iter() {
+            let Ok(relative) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            let components: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into())
+                .collect();
+            insert(&mut nodes, &self.root, &components, path);
+        }
+        sort(&mut nodes);
+        nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileTreeNode, Workspace};
+    use crate::test_util::TempDir;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    #[test]
+    fn scan_workspace() {
+        let dir = TempDir::new("workspace");
+        let root = dir.path();
+        fs::create_dir_all(root.join("guide/advanced")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        for file in [
+            "zebra.md",
+            "Readme.md",
+            "guide/install.markdown",
+            "guide/advanced/tuning.md",
+            "guide/notes.txt",
+            ".git/HEAD.md",
+        ] {
+            fs::write(root.join(file), "# Title").unwrap();
+        }
+        // A link to an ancestor would make the scan endless, linked files are listed
+        std::os::unix::fs::symlink(root, root.join("guide/loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("zebra.md"), root.join("guide/zebra.md")).unwrap();
+
+        let workspace = Workspace::scan(root).unwrap();
+        let root = root.canonicalize().unwrap();
+        assert_eq!(workspace.files.len(), 5);
+        assert_eq!(workspace.default_file(), Some(&root.join("Readme.md")));
+
+        let file = |name: &str, path: &str| FileTreeNode::File {
+            name: name.into(),
+            path: root.join(path),
+        };
+        assert_eq!(
+            workspace.tree(),
+            vec![
+                FileTreeNode::Dir {
+                    name: "guide".into(),
+                    path: root.join("guide"),
+                    children: vec![
+                        FileTreeNode::Dir {
+                            name: "advanced".into(),
+                            path: root.join("guide/advanced"),
+                            children: vec![file("tuning.md", "guide/advanced/tuning.md")],
+                        },
+                        file("install.markdown", "guide/install.markdown"),
+                        file("zebra.md", "guide/zebra.md"),
+                    ],
+                },
+                file("Readme.md", "Readme.md"),
+                file("zebra.md", "zebra.md"),
+            ]
+        );
+
+        let listed = Workspace::from_files(&[
+            root.join("guide/advanced/tuning.md"),
+            root.join("guide/install.markdown"),
+        ])
+        .unwrap();
+        assert_eq!(listed.root, root.join("guide"));
+        assert_eq!(listed.files[0], root.join("guide/advanced/tuning.md"));
+        assert!(Workspace::from_files(&[root.join("missing.md")]).is_err());
+        // Directories are opened on their own
+        assert!(Workspace::from_files(&[root.join("guide"), root.join("guide/advanced")]).is_err());
+        assert!(Workspace::from_files(&[root.join("Readme.md"), root.join("guide")]).is_err());
+
+        // An unreadable directory doesn't stop the scan
+        fs::create_dir_all(root.join("locked")).unwrap();
+        fs::write(root.join("locked/secret.md"), "# Secret").unwrap();
+        fs::set_permissions(root.join("locked"), fs::Permissions::from_mode(0o000)).unwrap();
+        // Permissions don't apply to root
+        let readable = fs::read_dir(root.join("locked")).is_ok();
+        let scanned = Workspace::scan(&root);
+        fs::set_permissions(root.join("locked"), fs::Permissions::from_mode(0o755)).unwrap();
+        let scanned = scanned.unwrap();
+        assert!(scanned.files.contains(&root.join("Readme.md")));
+        assert_eq!(
+            scanned.files.contains(&root.join("locked/secret.md")),
+            readable
+        );
+
+        let empty = Workspace {
+            root: PathBuf::from("/nowhere"),
+            files: vec![],
+        };
+        assert_eq!(empty.default_file(), None);
+    }
+}